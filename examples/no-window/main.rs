@@ -1,8 +1,8 @@
 mod snapshot;
 
-use std::{thread, time::Instant};
+use std::thread;
 
-use saunter::{snapshot::Snapshot, tickloop::TickLoop};
+use saunter::tickloop::TickLoop;
 use snapshot::NoWindowSnapshot;
 
 const TPS: f32 = 30.0;
@@ -14,16 +14,16 @@ fn main() {
         simplelog::TerminalMode::Mixed,
         simplelog::ColorChoice::Auto,
     )
-    .unwrap_or(println!("Failed to initialize logger"));
+    .unwrap_or_else(|_| println!("Failed to initialize logger"));
 
     let mut val = 1.0;
 
-    let (mut tick_loop, event_sender, _, ticks) = TickLoop::init(
+    let (mut tick_loop, event_sender, ctrl, ticks) = TickLoop::init(
         move |_dt, _events, _ctrl, time| {
             val = 1.0 - val;
             log::info!("ticked {}", val);
 
-            Ok(NoWindowSnapshot { val, time })
+            Ok(NoWindowSnapshot::new(time, val))
         },
         TPS,
     );
@@ -37,10 +37,8 @@ fn main() {
 
         let read_ticks = ticks.read().unwrap();
 
-        if let Some(last) = &read_ticks.last_snapshot {
-            let mapped_t = ((last.get_time().elapsed().as_secs_f32() * TPS as f32) - 1.0).max(0.0); //subtract 1 to get the previous tick
-            if let Ok(lerped) = read_ticks.interpolate_ticks(mapped_t, saunter::interpolate::linear)
-            {
+        if let Ok(alpha) = read_ticks.alpha(ctrl.clock()) {
+            if let Ok(lerped) = read_ticks.interpolate_ticks(alpha, saunter::interpolate::linear) {
                 log::info!("{}", lerped.val);
             }
         }
@@ -1,10 +1,8 @@
 mod tick;
 use tick::WinitTick;
 
-use saunter::snapshot::Snapshot;
 use saunter::tickloop::TickLoop;
 use std::thread;
-use std::time::Instant;
 use winit::event::Event;
 
 const TPS: f32 = 66.0;
@@ -16,7 +14,7 @@ fn main() {
         simplelog::TerminalMode::Mixed,
         simplelog::ColorChoice::Auto,
     )
-    .unwrap_or(println!("Failed to initialize logger"));
+    .unwrap_or_else(|_| println!("Failed to initialize logger"));
 
     let mut val = 1.0;
 
@@ -60,12 +58,9 @@ fn main() {
 
             let read_ticks = snapshots.read().unwrap();
 
-            if let Some(last) = &read_ticks.last_snapshot {
-                let mapped_t =
-                    ((last.get_time().elapsed().as_secs_f32() * TPS as f32) - 1.0).max(0.0); //subtract 1 to get the previous tick
-
+            if let Ok(alpha) = read_ticks.alpha(ctrl.clock()) {
                 if let Ok(lerped) =
-                    read_ticks.interpolate_ticks(mapped_t, saunter::interpolate::linear)
+                    read_ticks.interpolate_ticks(alpha, saunter::interpolate::linear)
                 {
                     let _lerped = lerped;
                 }
@@ -0,0 +1,149 @@
+//! Async integration, available with the `async` feature.
+//! A [`TickLoopHandle`] lets async code await snapshots as they are published, or use them as a [`Stream`],
+//! [`TickLoop::run_async`] drives the loop on any runtime's timer, and [`EventSender::send_async`](crate::event::EventSender::send_async) waits for room in a full channel without blocking a worker thread.
+//! Nothing here depends on a specific executor.
+
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, RwLock};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+use futures_core::Stream;
+
+use crate::snapshot::{Snapshot, Snapshots};
+use crate::tickloop::{Step, TickLoop, TickLoopControl};
+
+/// Wakes the tasks waiting for a snapshot whenever the tick loop publishes one.
+pub(crate) struct SnapshotWatch {
+    state: Mutex<WatchState>,
+}
+
+struct WatchState {
+    /// The number of snapshots published so far.
+    version: u64,
+    closed: bool,
+    wakers: Vec<Waker>,
+}
+
+impl SnapshotWatch {
+    pub(crate) fn new() -> Self {
+        SnapshotWatch {
+            state: Mutex::new(WatchState {
+                version: 0,
+                closed: false,
+                wakers: Vec::new(),
+            }),
+        }
+    }
+
+    pub(crate) fn publish(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.version += 1;
+        state.wakers.drain(..).for_each(Waker::wake);
+    }
+
+    pub(crate) fn close(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.closed = true;
+        state.wakers.drain(..).for_each(Waker::wake);
+    }
+
+    /// Resolves to the latest version once it is newer than `seen`, or `None` once the loop has stopped.
+    fn poll_version(&self, seen: u64, cx: &mut Context<'_>) -> Poll<Option<u64>> {
+        let mut state = self.state.lock().unwrap();
+        if state.version > seen {
+            Poll::Ready(Some(state.version))
+        } else if state.closed {
+            Poll::Ready(None)
+        } else {
+            state.wakers.retain(|waker| !waker.will_wake(cx.waker()));
+            state.wakers.push(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+/// Gives async code access to the snapshots and control of a [`TickLoop`].
+/// Can be used as a [`Stream`] of snapshots, which ends once the loop stops.
+///
+/// Waiting always yields the latest snapshot. Snapshots published while nobody is waiting are skipped.
+pub struct TickLoopHandle<S: Snapshot> {
+    control: TickLoopControl,
+    snapshots: Arc<RwLock<Snapshots<S>>>,
+    watch: Arc<SnapshotWatch>,
+    /// The version of the last snapshot this handle returned.
+    seen: u64,
+}
+
+impl<S: Snapshot + Clone> TickLoopHandle<S> {
+    pub fn control(&self) -> &TickLoopControl {
+        &self.control
+    }
+
+    pub fn snapshots(&self) -> &Arc<RwLock<Snapshots<S>>> {
+        &self.snapshots
+    }
+
+    /// Waits for the next snapshot published after the last one this handle returned.
+    /// Returns `None` once the loop has stopped.
+    pub async fn next_snapshot(&mut self) -> Option<S> {
+        poll_fn(|cx| self.poll_snapshot(cx)).await
+    }
+
+    fn poll_snapshot(&mut self, cx: &mut Context<'_>) -> Poll<Option<S>> {
+        self.watch.poll_version(self.seen, cx).map(|version| {
+            self.seen = version?;
+            self.snapshots.read().unwrap().new_snapshot.clone()
+        })
+    }
+}
+
+impl<S: Snapshot> Clone for TickLoopHandle<S> {
+    fn clone(&self) -> Self {
+        TickLoopHandle {
+            control: self.control.clone(),
+            snapshots: self.snapshots.clone(),
+            watch: self.watch.clone(),
+            seen: self.seen,
+        }
+    }
+}
+
+impl<S: Snapshot + Clone> Stream for TickLoopHandle<S> {
+    type Item = S;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<S>> {
+        self.get_mut().poll_snapshot(cx)
+    }
+}
+
+impl<S: Snapshot, E: Send> TickLoop<S, E> {
+    /// Returns a [`TickLoopHandle`] to await this loop's snapshots.
+    pub fn handle(&self) -> TickLoopHandle<S> {
+        TickLoopHandle {
+            control: self.control(),
+            snapshots: self.snapshots(),
+            watch: self.watch(),
+            seen: 0,
+        }
+    }
+
+    /// Runs the loop like [`start`](TickLoop::start), but waits between ticks with `sleep` instead of blocking the thread.
+    /// Pass the sleep function of your runtime, for example `tokio::time::sleep`.
+    pub async fn run_async<F, Fut>(&mut self, mut sleep: F)
+    where
+        F: FnMut(Duration) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        loop {
+            match self.step() {
+                Step::Wait(duration) => sleep(duration).await,
+                // There is no runtime-independent way to park a task, so check again every tick.
+                Step::Paused => sleep(self.tick_length).await,
+                Step::Stopped => break,
+            }
+        }
+        self.finish();
+    }
+}
@@ -0,0 +1,262 @@
+//! Color types that interpolate in a sensible color space.
+//! Interpolating sRGB components directly produces dark, muddy midpoints, because sRGB values are not proportional to light intensity.
+//! The types in this module store colors in a familiar form but implement [`Interpolate`] in a space better suited for blending:
+//! - [`Rgb`] and [`Rgba`] store sRGB components and interpolate in linear RGB.
+//! - [`Oklab`] interpolates in the perceptually uniform Oklab space.
+//! - [`Hsv`] interpolates hue along the shortest path around the color wheel.
+//!
+//! All of them can be used as fields in a snapshot that derives `Interpolate`.
+
+use crate::interpolate::{lerp, Interpolate};
+
+/// Converts an sRGB encoded component to linear light.
+pub fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Converts a linear light component to sRGB encoding.
+pub fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// An opaque sRGB color with components in the range `0.0..=1.0`.
+/// Interpolates in linear RGB.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rgb {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+}
+
+impl Rgb {
+    pub fn new(r: f32, g: f32, b: f32) -> Self {
+        Rgb { r, g, b }
+    }
+
+    /// Creates a color from 8 bit sRGB components.
+    pub fn from_rgb8(r: u8, g: u8, b: u8) -> Self {
+        Rgb::new(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0)
+    }
+
+    /// Returns the color as 8 bit sRGB components.
+    pub fn to_rgb8(self) -> [u8; 3] {
+        let to_u8 = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        [to_u8(self.r), to_u8(self.g), to_u8(self.b)]
+    }
+
+    /// Returns the linear light components of the color.
+    pub fn to_linear(self) -> [f32; 3] {
+        [
+            srgb_to_linear(self.r),
+            srgb_to_linear(self.g),
+            srgb_to_linear(self.b),
+        ]
+    }
+
+    /// Creates a color from linear light components.
+    pub fn from_linear([r, g, b]: [f32; 3]) -> Self {
+        Rgb::new(linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b))
+    }
+
+    /// Adds an alpha channel to the color.
+    pub fn with_alpha(self, a: f32) -> Rgba {
+        Rgba::new(self.r, self.g, self.b, a)
+    }
+}
+
+impl Interpolate for Rgb {
+    fn interpolate(start: &Self, end: &Self, t: f32, interpolation: impl Fn(f32) -> f32) -> Self {
+        let t = interpolation(t);
+        let start = start.to_linear();
+        let end = end.to_linear();
+        Rgb::from_linear([
+            lerp(start[0], end[0], t),
+            lerp(start[1], end[1], t),
+            lerp(start[2], end[2], t),
+        ])
+    }
+}
+
+/// An sRGB color with a straight (not premultiplied) alpha channel.
+/// The color interpolates in linear RGB and the alpha interpolates linearly.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rgba {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Rgba {
+    pub fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Rgba { r, g, b, a }
+    }
+
+    /// Creates a color from 8 bit sRGB components and an 8 bit alpha.
+    pub fn from_rgba8(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Rgb::from_rgb8(r, g, b).with_alpha(a as f32 / 255.0)
+    }
+
+    /// Returns the color without its alpha channel.
+    pub fn rgb(self) -> Rgb {
+        Rgb::new(self.r, self.g, self.b)
+    }
+}
+
+impl Interpolate for Rgba {
+    fn interpolate(start: &Self, end: &Self, t: f32, interpolation: impl Fn(f32) -> f32) -> Self {
+        let t = interpolation(t);
+        Rgb::interpolate(&start.rgb(), &end.rgb(), t, crate::interpolate::linear)
+            .with_alpha(lerp(start.a, end.a, t))
+    }
+}
+
+/// A color in the Oklab color space.
+/// Interpolating in Oklab keeps perceived lightness and chroma even across the blend.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Oklab {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+}
+
+impl Oklab {
+    pub fn new(l: f32, a: f32, b: f32) -> Self {
+        Oklab { l, a, b }
+    }
+}
+
+// The matrices are the reference values published with Oklab, kept at full precision.
+#[allow(clippy::excessive_precision)]
+impl From<Rgb> for Oklab {
+    fn from(color: Rgb) -> Self {
+        let [r, g, b] = color.to_linear();
+
+        let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+        let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+        let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+
+        Oklab {
+            l: 0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+            a: 1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+            b: 0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+        }
+    }
+}
+
+#[allow(clippy::excessive_precision)]
+impl From<Oklab> for Rgb {
+    fn from(color: Oklab) -> Self {
+        let l = color.l + 0.3963377774 * color.a + 0.2158037573 * color.b;
+        let m = color.l - 0.1055613458 * color.a - 0.0638541728 * color.b;
+        let s = color.l - 0.0894841775 * color.a - 1.2914855480 * color.b;
+
+        let (l, m, s) = (l * l * l, m * m * m, s * s * s);
+
+        Rgb::from_linear([
+            4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+            -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+            -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+        ])
+    }
+}
+
+impl Interpolate for Oklab {
+    fn interpolate(start: &Self, end: &Self, t: f32, interpolation: impl Fn(f32) -> f32) -> Self {
+        let t = interpolation(t);
+        Oklab::new(
+            lerp(start.l, end.l, t),
+            lerp(start.a, end.a, t),
+            lerp(start.b, end.b, t),
+        )
+    }
+}
+
+/// A color in the HSV color space.
+/// The hue is in degrees (`0.0..360.0`), saturation and value are in the range `0.0..=1.0`.
+/// Hue is interpolated along the shortest path around the color wheel.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hsv {
+    pub h: f32,
+    pub s: f32,
+    pub v: f32,
+}
+
+impl Hsv {
+    pub fn new(h: f32, s: f32, v: f32) -> Self {
+        Hsv { h, s, v }
+    }
+}
+
+impl From<Rgb> for Hsv {
+    fn from(color: Rgb) -> Self {
+        let max = color.r.max(color.g).max(color.b);
+        let min = color.r.min(color.g).min(color.b);
+        let delta = max - min;
+
+        let h = if delta == 0.0 {
+            0.0
+        } else if max == color.r {
+            60.0 * ((color.g - color.b) / delta)
+        } else if max == color.g {
+            60.0 * ((color.b - color.r) / delta + 2.0)
+        } else {
+            60.0 * ((color.r - color.g) / delta + 4.0)
+        };
+        let s = if max == 0.0 { 0.0 } else { delta / max };
+
+        Hsv::new(h.rem_euclid(360.0), s, max)
+    }
+}
+
+impl From<Hsv> for Rgb {
+    fn from(color: Hsv) -> Self {
+        let c = color.v * color.s;
+        let h = color.h.rem_euclid(360.0) / 60.0;
+        let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+        let m = color.v - c;
+
+        let (r, g, b) = match h as u32 {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+
+        Rgb::new(r + m, g + m, b + m)
+    }
+}
+
+impl Interpolate for Hsv {
+    fn interpolate(start: &Self, end: &Self, t: f32, interpolation: impl Fn(f32) -> f32) -> Self {
+        let t = interpolation(t);
+
+        // A grey has no meaningful hue, so borrow the hue of the other color instead of sweeping through the wheel.
+        let (start_h, end_h) = match (start.s == 0.0, end.s == 0.0) {
+            (true, false) => (end.h, end.h),
+            (false, true) => (start.h, start.h),
+            _ => (start.h, end.h),
+        };
+        let delta = (end_h - start_h + 540.0).rem_euclid(360.0) - 180.0;
+
+        Hsv::new(
+            (start_h + delta * t).rem_euclid(360.0),
+            lerp(start.s, end.s, t),
+            lerp(start.v, end.v, t),
+        )
+    }
+}
@@ -0,0 +1,561 @@
+//! Contains the [`Easing`] enum.
+//! The easing functions in [`interpolate`] are plain functions, which makes them hard to store, compare or load from a config file.
+//! [`Easing`] names each of them, can wrap custom functions, and can build new easings out of existing ones.
+//!
+//! Easings are written and parsed by name, for example `"ease_in_out_cubic"` or `"reverse(ease_in_quad)"`.
+//!
+//! This module also contains [`CubicBezier`] and [`Spring`], easings that are configured at runtime.
+
+use std::{
+    fmt::{Debug, Display, Formatter},
+    str::FromStr,
+    sync::Arc,
+};
+
+use crate::{error::SaunterError, interpolate};
+
+macro_rules! easings {
+    ($($variant:ident => $function:ident),* $(,)?) => {
+        /// A named easing function.
+        /// Use [`ease`](Easing::ease) or [`as_fn`](Easing::as_fn) to apply it, for example when calling [`interpolate_ticks`](crate::snapshot::Snapshots::interpolate_ticks).
+        #[derive(Clone, Default)]
+        pub enum Easing {
+            #[default]
+            $(
+                #[doc = concat!("[`", stringify!($function), "`](interpolate::", stringify!($function), ")")]
+                $variant,
+            )*
+            /// [`steps`](interpolate::steps)
+            Steps(u32),
+            /// [`back`](interpolate::back) with a custom overshoot.
+            Back(f32),
+            /// [`power`](interpolate::power) with a custom exponent.
+            Power(f32),
+            /// [`elastic`](interpolate::elastic) with a custom amplitude and period.
+            Elastic { amplitude: f32, period: f32 },
+            /// A CSS style cubic bezier curve.
+            CubicBezier(CubicBezier),
+            /// A damped spring.
+            Spring(Spring),
+            /// A user provided easing function. Custom easings can not be serialized.
+            Custom(Arc<dyn Fn(f32) -> f32 + Send + Sync>),
+            /// Plays the inner easing backwards.
+            Reverse(Box<Easing>),
+            /// Plays the inner easing forwards in the first half and backwards in the second half.
+            Mirror(Box<Easing>),
+            /// Plays the first easing in the first half and the second easing in the second half.
+            Chain(Box<Easing>, Box<Easing>),
+            /// Multiplies the output of the inner easing by a factor.
+            Scale(Box<Easing>, f32),
+            /// An out easing built from an in easing. See [`out_from`](Easing::out_from).
+            Out(Box<Easing>),
+            /// An in-out easing built from an in easing. See [`in_out_from`](Easing::in_out_from).
+            InOut(Box<Easing>),
+        }
+
+        impl Easing {
+            fn from_name(name: &str) -> Option<Easing> {
+                match name {
+                    $(stringify!($function) => Some(Easing::$variant),)*
+                    _ => None,
+                }
+            }
+
+            fn name(&self) -> &'static str {
+                match self {
+                    $(Easing::$variant => stringify!($function),)*
+                    Easing::Steps(_) => "steps",
+                    Easing::Back(_) => "back",
+                    Easing::Power(_) => "power",
+                    Easing::Elastic { .. } => "elastic",
+                    Easing::CubicBezier(_) => "cubic_bezier",
+                    Easing::Spring(_) => "spring",
+                    Easing::Custom(_) => "custom",
+                    Easing::Reverse(_) => "reverse",
+                    Easing::Mirror(_) => "mirror",
+                    Easing::Chain(_, _) => "chain",
+                    Easing::Scale(_, _) => "scale",
+                    Easing::Out(_) => "out",
+                    Easing::InOut(_) => "in_out",
+                }
+            }
+
+            /// Applies the easing to `t`.
+            pub fn ease(&self, t: f32) -> f32 {
+                match self {
+                    $(Easing::$variant => interpolate::$function(t),)*
+                    Easing::Steps(steps) => interpolate::steps(*steps)(t),
+                    Easing::Back(overshoot) => interpolate::back(*overshoot)(t),
+                    Easing::Power(exponent) => interpolate::power(*exponent)(t),
+                    Easing::Elastic { amplitude, period } => {
+                        interpolate::elastic(*amplitude, *period)(t)
+                    }
+                    Easing::CubicBezier(curve) => curve.ease(t),
+                    Easing::Spring(spring) => spring.ease(t),
+                    Easing::Custom(function) => function(t),
+                    Easing::Reverse(inner) => inner.ease(1.0 - t),
+                    Easing::Mirror(inner) => {
+                        if t < 0.5 {
+                            inner.ease(2.0 * t)
+                        } else {
+                            inner.ease(2.0 - 2.0 * t)
+                        }
+                    }
+                    Easing::Chain(first, second) => {
+                        if t < 0.5 {
+                            first.ease(2.0 * t) / 2.0
+                        } else {
+                            0.5 + second.ease(2.0 * t - 1.0) / 2.0
+                        }
+                    }
+                    Easing::Scale(inner, factor) => inner.ease(t) * factor,
+                    Easing::Out(inner) => 1.0 - inner.ease(1.0 - t),
+                    Easing::InOut(inner) => {
+                        if t < 0.5 {
+                            inner.ease(2.0 * t) / 2.0
+                        } else {
+                            1.0 - inner.ease(2.0 - 2.0 * t) / 2.0
+                        }
+                    }
+                }
+            }
+        }
+    };
+}
+
+easings! {
+    Linear => linear,
+    EaseInSine => ease_in_sine,
+    EaseOutSine => ease_out_sine,
+    EaseInOutSine => ease_in_out_sine,
+    EaseInQuad => ease_in_quad,
+    EaseOutQuad => ease_out_quad,
+    EaseInOutQuad => ease_in_out_quad,
+    EaseInCubic => ease_in_cubic,
+    EaseOutCubic => ease_out_cubic,
+    EaseInOutCubic => ease_in_out_cubic,
+    EaseInQuart => ease_in_quart,
+    EaseOutQuart => ease_out_quart,
+    EaseInOutQuart => ease_in_out_quart,
+    EaseInQuint => ease_in_quint,
+    EaseOutQuint => ease_out_quint,
+    EaseInOutQuint => ease_in_out_quint,
+    EaseInExpo => ease_in_expo,
+    EaseOutExpo => ease_out_expo,
+    EaseInOutExpo => ease_in_out_expo,
+    EaseInCirc => ease_in_circ,
+    EaseOutCirc => ease_out_circ,
+    EaseInOutCirc => ease_in_out_circ,
+    EaseInBack => ease_in_back,
+    EaseOutBack => ease_out_back,
+    EaseInOutBack => ease_in_out_back,
+    EaseInElastic => ease_in_elastic,
+    EaseOutElastic => ease_out_elastic,
+    EaseInOutElastic => ease_in_out_elastic,
+    EaseInBounce => ease_in_bounce,
+    EaseOutBounce => ease_out_bounce,
+    EaseInOutBounce => ease_in_out_bounce,
+}
+
+impl Easing {
+    /// Wraps a custom easing function.
+    pub fn custom(function: impl Fn(f32) -> f32 + Send + Sync + 'static) -> Self {
+        Easing::Custom(Arc::new(function))
+    }
+
+    /// Builds an out easing from an in easing by flipping it, so `Easing::out_from(Easing::Back(2.0))` overshoots at the end.
+    pub fn out_from(ease_in: Easing) -> Self {
+        Easing::Out(Box::new(ease_in))
+    }
+
+    /// Builds an in-out easing from an in easing.
+    /// The first half plays `ease_in` and the second half plays it flipped, so that it eases out.
+    pub fn in_out_from(ease_in: Easing) -> Self {
+        Easing::InOut(Box::new(ease_in))
+    }
+
+    /// Returns an easing that plays this one backwards.
+    pub fn reverse(self) -> Self {
+        Easing::Reverse(Box::new(self))
+    }
+
+    /// Returns an easing that plays this one forwards and then backwards.
+    pub fn mirror(self) -> Self {
+        Easing::Mirror(Box::new(self))
+    }
+
+    /// Returns an easing that plays this one in the first half and `next` in the second half.
+    pub fn chain(self, next: Easing) -> Self {
+        Easing::Chain(Box::new(self), Box::new(next))
+    }
+
+    /// Returns an easing whose output is this one's multiplied by `factor`.
+    pub fn scale(self, factor: f32) -> Self {
+        Easing::Scale(Box::new(self), factor)
+    }
+
+    /// Borrows the easing as a closure, so it can be passed anywhere an `impl Fn(f32) -> f32` is accepted.
+    pub fn as_fn(&self) -> impl Fn(f32) -> f32 + '_ {
+        move |t| self.ease(t)
+    }
+}
+
+impl From<CubicBezier> for Easing {
+    fn from(curve: CubicBezier) -> Self {
+        Easing::CubicBezier(curve)
+    }
+}
+
+impl From<Spring> for Easing {
+    fn from(spring: Spring) -> Self {
+        Easing::Spring(spring)
+    }
+}
+
+impl Debug for Easing {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Easing({})", self)
+    }
+}
+
+impl PartialEq for Easing {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Easing::Steps(a), Easing::Steps(b)) => a == b,
+            (Easing::Back(a), Easing::Back(b)) | (Easing::Power(a), Easing::Power(b)) => a == b,
+            (
+                Easing::Elastic { amplitude, period },
+                Easing::Elastic {
+                    amplitude: other_amplitude,
+                    period: other_period,
+                },
+            ) => amplitude == other_amplitude && period == other_period,
+            (Easing::CubicBezier(a), Easing::CubicBezier(b)) => a == b,
+            (Easing::Spring(a), Easing::Spring(b)) => a == b,
+            (Easing::Custom(a), Easing::Custom(b)) => Arc::ptr_eq(a, b),
+            (Easing::Reverse(a), Easing::Reverse(b))
+            | (Easing::Mirror(a), Easing::Mirror(b))
+            | (Easing::Out(a), Easing::Out(b))
+            | (Easing::InOut(a), Easing::InOut(b)) => a == b,
+            (Easing::Chain(a1, a2), Easing::Chain(b1, b2)) => a1 == b1 && a2 == b2,
+            (Easing::Scale(a, a_factor), Easing::Scale(b, b_factor)) => {
+                a == b && a_factor == b_factor
+            }
+            (Easing::Steps(_), _)
+            | (Easing::Back(_), _)
+            | (Easing::Power(_), _)
+            | (Easing::Elastic { .. }, _)
+            | (Easing::CubicBezier(_), _)
+            | (Easing::Spring(_), _)
+            | (Easing::Custom(_), _)
+            | (Easing::Reverse(_), _)
+            | (Easing::Mirror(_), _)
+            | (Easing::Out(_), _)
+            | (Easing::InOut(_), _)
+            | (Easing::Chain(_, _), _)
+            | (Easing::Scale(_, _), _) => false,
+            _ => self.name() == other.name(),
+        }
+    }
+}
+
+impl Display for Easing {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Easing::Steps(steps) => write!(f, "{}({})", self.name(), steps),
+            Easing::Back(value) | Easing::Power(value) => write!(f, "{}({})", self.name(), value),
+            Easing::Elastic { amplitude, period } => {
+                write!(f, "{}({}, {})", self.name(), amplitude, period)
+            }
+            Easing::CubicBezier(curve) => write!(
+                f,
+                "{}({}, {}, {}, {})",
+                self.name(),
+                curve.x1,
+                curve.y1,
+                curve.x2,
+                curve.y2
+            ),
+            Easing::Spring(spring) => write!(
+                f,
+                "{}({}, {})",
+                self.name(),
+                spring.damping_ratio,
+                spring.frequency
+            ),
+            Easing::Reverse(inner)
+            | Easing::Mirror(inner)
+            | Easing::Out(inner)
+            | Easing::InOut(inner) => write!(f, "{}({})", self.name(), inner),
+            Easing::Chain(first, second) => write!(f, "{}({}, {})", self.name(), first, second),
+            Easing::Scale(inner, factor) => write!(f, "{}({}, {})", self.name(), inner, factor),
+            _ => write!(f, "{}", self.name()),
+        }
+    }
+}
+
+impl FromStr for Easing {
+    type Err = SaunterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || SaunterError::InvalidEasing(s.to_string());
+        let s = s.trim();
+
+        let Some((name, rest)) = s.split_once('(') else {
+            return Easing::from_name(s).ok_or_else(invalid);
+        };
+        let args = rest.strip_suffix(')').ok_or_else(invalid)?;
+        let args = split_args(args).ok_or_else(invalid)?;
+
+        let easing = |arg: &str| arg.parse::<Easing>().map(Box::new);
+        let number = |arg: &str| arg.trim().parse::<f32>().map_err(|_| invalid());
+
+        match (name.trim(), args.as_slice()) {
+            ("steps", [steps]) => Ok(Easing::Steps(steps.trim().parse().map_err(|_| invalid())?)),
+            ("back", [overshoot]) => Ok(Easing::Back(number(overshoot)?)),
+            ("power", [exponent]) => Ok(Easing::Power(number(exponent)?)),
+            ("elastic", [amplitude, period]) => Ok(Easing::Elastic {
+                amplitude: number(amplitude)?,
+                period: number(period)?,
+            }),
+            ("cubic_bezier", [x1, y1, x2, y2]) => Ok(Easing::CubicBezier(CubicBezier::new(
+                number(x1)?,
+                number(y1)?,
+                number(x2)?,
+                number(y2)?,
+            ))),
+            ("spring", [damping_ratio, frequency]) => Ok(Easing::Spring(Spring::new(
+                number(damping_ratio)?,
+                number(frequency)?,
+            ))),
+            ("reverse", [inner]) => Ok(Easing::Reverse(easing(inner)?)),
+            ("mirror", [inner]) => Ok(Easing::Mirror(easing(inner)?)),
+            ("out", [inner]) => Ok(Easing::Out(easing(inner)?)),
+            ("in_out", [inner]) => Ok(Easing::InOut(easing(inner)?)),
+            ("chain", [first, second]) => Ok(Easing::Chain(easing(first)?, easing(second)?)),
+            ("scale", [inner, factor]) => Ok(Easing::Scale(easing(inner)?, number(factor)?)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// A cubic bezier easing curve, defined the same way as the CSS `cubic-bezier(x1, y1, x2, y2)` timing function.
+/// The curve starts at `(0, 0)` and ends at `(1, 1)`, with two control points in between.
+/// The x coordinates of the control points are clamped to `0.0..=1.0` so the curve is a function of time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CubicBezier {
+    pub x1: f32,
+    pub y1: f32,
+    pub x2: f32,
+    pub y2: f32,
+}
+
+impl CubicBezier {
+    /// The CSS `ease` timing function.
+    pub const EASE: CubicBezier = CubicBezier::new(0.25, 0.1, 0.25, 1.0);
+    /// The CSS `ease-in` timing function.
+    pub const EASE_IN: CubicBezier = CubicBezier::new(0.42, 0.0, 1.0, 1.0);
+    /// The CSS `ease-out` timing function.
+    pub const EASE_OUT: CubicBezier = CubicBezier::new(0.0, 0.0, 0.58, 1.0);
+    /// The CSS `ease-in-out` timing function.
+    pub const EASE_IN_OUT: CubicBezier = CubicBezier::new(0.42, 0.0, 0.58, 1.0);
+
+    pub const fn new(x1: f32, y1: f32, x2: f32, y2: f32) -> Self {
+        CubicBezier { x1, y1, x2, y2 }
+    }
+
+    /// Evaluates one coordinate of the curve at curve parameter `s`.
+    fn sample(p1: f32, p2: f32, s: f32) -> f32 {
+        let c = 3.0 * p1;
+        let b = 3.0 * (p2 - p1) - c;
+        let a = 1.0 - c - b;
+        ((a * s + b) * s + c) * s
+    }
+
+    /// Evaluates the derivative of one coordinate of the curve at curve parameter `s`.
+    fn sample_derivative(p1: f32, p2: f32, s: f32) -> f32 {
+        let c = 3.0 * p1;
+        let b = 3.0 * (p2 - p1) - c;
+        let a = 1.0 - c - b;
+        (3.0 * a * s + 2.0 * b) * s + c
+    }
+
+    /// Finds the curve parameter whose x coordinate is `x`.
+    /// Newton's method converges in a few iterations for most curves, and bisection is used when it doesn't.
+    fn solve_x(&self, x: f32) -> f32 {
+        const EPSILON: f32 = 1e-6;
+        let (x1, x2) = (self.x1.clamp(0.0, 1.0), self.x2.clamp(0.0, 1.0));
+
+        let mut s = x;
+        for _ in 0..8 {
+            let error = Self::sample(x1, x2, s) - x;
+            if error.abs() < EPSILON {
+                return s;
+            }
+            let derivative = Self::sample_derivative(x1, x2, s);
+            if derivative.abs() < EPSILON {
+                break;
+            }
+            s -= error / derivative;
+        }
+
+        let (mut low, mut high) = (0.0, 1.0);
+        s = x;
+        while high - low > EPSILON {
+            let sampled = Self::sample(x1, x2, s);
+            if (sampled - x).abs() < EPSILON {
+                break;
+            }
+            if sampled < x {
+                low = s;
+            } else {
+                high = s;
+            }
+            s = (low + high) / 2.0;
+        }
+        s
+    }
+
+    /// Applies the easing to `t`.
+    pub fn ease(&self, t: f32) -> f32 {
+        if t <= 0.0 {
+            0.0
+        } else if t >= 1.0 {
+            1.0
+        } else {
+            Self::sample(self.y1, self.y2, self.solve_x(t))
+        }
+    }
+
+    /// Returns the easing as a closure, so it can be passed anywhere an `impl Fn(f32) -> f32` is accepted.
+    pub fn as_fn(self) -> impl Fn(f32) -> f32 + Send + Sync {
+        move |t| self.ease(t)
+    }
+}
+
+/// A damped spring easing. The spring starts at rest at `0.0` and is pulled towards `1.0`.
+/// - A `damping_ratio` of `1.0` is critically damped and reaches `1.0` as fast as possible without overshooting.
+/// - A `damping_ratio` below `1.0` is under-damped and oscillates around `1.0` before settling.
+/// - A `damping_ratio` above `1.0` is over-damped and approaches `1.0` slowly.
+///
+/// `frequency` is the number of undamped oscillations over the whole easing.
+/// Springs never settle exactly, so the easing snaps to `1.0` at the end, and the frequency should be high enough for the spring to settle before `t` reaches `1.0`.
+/// A spring without a positive frequency does not move until the end.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spring {
+    pub damping_ratio: f32,
+    pub frequency: f32,
+}
+
+impl Spring {
+    pub const fn new(damping_ratio: f32, frequency: f32) -> Self {
+        Spring {
+            damping_ratio,
+            frequency,
+        }
+    }
+
+    /// Creates a critically damped spring.
+    pub const fn critically_damped(frequency: f32) -> Self {
+        Spring::new(1.0, frequency)
+    }
+
+    /// Creates a spring from physical properties.
+    /// `duration` is the length of the easing in seconds.
+    pub fn from_physical(mass: f32, stiffness: f32, damping: f32, duration: f32) -> Self {
+        let angular_frequency = (stiffness / mass).sqrt();
+        Spring::new(
+            damping / (2.0 * (stiffness * mass).sqrt()),
+            angular_frequency * duration / (2.0 * std::f32::consts::PI),
+        )
+    }
+
+    /// Applies the easing to `t`.
+    pub fn ease(&self, t: f32) -> f32 {
+        if t <= 0.0 || (self.frequency <= 0.0 && t < 1.0) {
+            return 0.0;
+        } else if t >= 1.0 {
+            return 1.0;
+        }
+
+        let omega = 2.0 * std::f32::consts::PI * self.frequency;
+        let zeta = self.damping_ratio.max(0.0);
+
+        if zeta < 1.0 {
+            let damped_omega = omega * (1.0 - zeta * zeta).sqrt();
+            1.0 - (-zeta * omega * t).exp()
+                * ((damped_omega * t).cos()
+                    + (zeta * omega / damped_omega) * (damped_omega * t).sin())
+        } else if zeta == 1.0 {
+            1.0 - (-omega * t).exp() * (1.0 + omega * t)
+        } else {
+            let root = (zeta * zeta - 1.0).sqrt();
+            let r1 = -omega * (zeta - root);
+            let r2 = -omega * (zeta + root);
+            1.0 - (r2 * (r1 * t).exp() - r1 * (r2 * t).exp()) / (r2 - r1)
+        }
+    }
+
+    /// Returns the easing as a closure, so it can be passed anywhere an `impl Fn(f32) -> f32` is accepted.
+    pub fn as_fn(self) -> impl Fn(f32) -> f32 + Send + Sync {
+        move |t| self.ease(t)
+    }
+}
+
+/// Splits a comma separated argument list, ignoring commas inside nested parentheses.
+fn split_args(args: &str) -> Option<Vec<&str>> {
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut split = Vec::new();
+
+    for (i, c) in args.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.checked_sub(1)?,
+            ',' if depth == 0 => {
+                split.push(&args[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return None;
+    }
+    split.push(&args[start..]);
+
+    Some(split)
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Easing {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        fn contains_custom(easing: &Easing) -> bool {
+            match easing {
+                Easing::Custom(_) => true,
+                Easing::Reverse(inner)
+                | Easing::Mirror(inner)
+                | Easing::Out(inner)
+                | Easing::InOut(inner)
+                | Easing::Scale(inner, _) => contains_custom(inner),
+                Easing::Chain(first, second) => contains_custom(first) || contains_custom(second),
+                _ => false,
+            }
+        }
+
+        if contains_custom(self) {
+            return Err(serde::ser::Error::custom(
+                "custom easings can not be serialized",
+            ));
+        }
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Easing {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
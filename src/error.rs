@@ -10,6 +10,11 @@ use std::{
 pub enum SaunterError {
     TooFewSnapshots,
     CouldNotCreateSnapshot,
+    InvalidEasing(String),
+    /// A message was sent from the tick loop, but there is no receiver for messages of its type.
+    MessageNotDelivered,
+    /// A message channel was requested for a type that already has one.
+    MessageChannelExists,
 }
 impl Error for SaunterError {}
 
@@ -18,6 +23,13 @@ impl Display for SaunterError {
         match self {
             SaunterError::TooFewSnapshots => write!(f, "Not enough ticks to lerp"),
             SaunterError::CouldNotCreateSnapshot => write!(f, "Could not create a tick"),
+            SaunterError::InvalidEasing(easing) => write!(f, "Invalid easing: {}", easing),
+            SaunterError::MessageNotDelivered => {
+                write!(f, "No receiver for messages of this type")
+            }
+            SaunterError::MessageChannelExists => {
+                write!(f, "There already is a channel for messages of this type")
+            }
         }
     }
 }
@@ -0,0 +1,527 @@
+//! Contains the event channel of the [`TickLoop`](crate::tickloop::TickLoop).
+//! Every event is sent in an [`Envelope`] that says which tick it should be delivered on,
+//! so inputs land on the same tick no matter how the sending thread and the tick loop are scheduled.
+//! Events for a tick are delivered in the order they were sent, and events for ticks that have already run are delivered on the next tick.
+//! A loop built on a [`TargetedReceiver`] gets the envelopes themselves, with the tick each event was meant for, so it can tell late events apart.
+//! An [`EventReducer`] can coalesce, filter or reorder each tick's events before the listener sees them.
+//! The channel can be bounded with an [`OverflowPolicy`] that decides what happens when it is full, so a stalled loop does not grow memory without limit.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, SendError};
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::Waker;
+use std::time::Instant;
+
+/// Which tick an event should be delivered on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EventTarget {
+    /// The next tick that runs after the event is received.
+    NextTick,
+    /// The tick with this index.
+    Tick(u64),
+    /// The first tick that starts at or after this time. Events stamped with [`Instant::now`] are delivered on the tick after the one that was running when they were sent.
+    Time(#[cfg_attr(feature = "serde", serde(with = "crate::time::serde_instant"))] Instant),
+}
+
+/// An event and the tick it should be delivered on.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Envelope<E> {
+    pub target: EventTarget,
+    pub event: E,
+}
+
+impl<E> Envelope<E> {
+    pub fn new(target: EventTarget, event: E) -> Self {
+        Envelope { target, event }
+    }
+
+    /// Returns the index of the tick the event is meant for, if its target is a tick.
+    /// Envelopes delivered by a [`TargetedReceiver`] always target a tick.
+    pub fn tick(&self) -> Option<u64> {
+        match self.target {
+            EventTarget::Tick(tick) => Some(tick),
+            _ => None,
+        }
+    }
+}
+
+type MergeFn<E> = dyn Fn(&E, &E) -> Option<E> + Send + Sync;
+
+/// What a bounded channel does with an event sent while it is full.
+pub enum OverflowPolicy<E> {
+    /// Waits until the tick loop has drained the channel.
+    Block,
+    /// Drops the event being sent.
+    DropNewest,
+    /// Drops the oldest event in the channel to make room.
+    DropOldest,
+    /// Merges the event into the newest event in the channel, for example to combine mouse moves.
+    /// The function returns `None` if the events can't be merged, in which case the event being sent is dropped.
+    /// Events are only merged when they are meant for the same [`EventTarget`], otherwise the event being sent is dropped as well,
+    /// so an event is never moved to another tick.
+    Coalesce(Arc<MergeFn<E>>),
+}
+
+impl<E> OverflowPolicy<E> {
+    /// Creates a [`Coalesce`](OverflowPolicy::Coalesce) policy from a merge function.
+    pub fn coalesce(merge: impl Fn(&E, &E) -> Option<E> + Send + Sync + 'static) -> Self {
+        OverflowPolicy::Coalesce(Arc::new(merge))
+    }
+}
+
+impl<E> Clone for OverflowPolicy<E> {
+    fn clone(&self) -> Self {
+        match self {
+            OverflowPolicy::Block => OverflowPolicy::Block,
+            OverflowPolicy::DropNewest => OverflowPolicy::DropNewest,
+            OverflowPolicy::DropOldest => OverflowPolicy::DropOldest,
+            OverflowPolicy::Coalesce(merge) => OverflowPolicy::Coalesce(merge.clone()),
+        }
+    }
+}
+
+impl<E> Debug for OverflowPolicy<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OverflowPolicy::Block => write!(f, "Block"),
+            OverflowPolicy::DropNewest => write!(f, "DropNewest"),
+            OverflowPolicy::DropOldest => write!(f, "DropOldest"),
+            OverflowPolicy::Coalesce(_) => write!(f, "Coalesce(..)"),
+        }
+    }
+}
+
+/// The overflow policy of a queue, over either the events or the whole envelopes.
+enum QueuePolicy<E> {
+    Events(OverflowPolicy<E>),
+    Envelopes(OverflowPolicy<Envelope<E>>),
+}
+
+impl<E> QueuePolicy<E> {
+    fn is_block(&self) -> bool {
+        matches!(
+            self,
+            QueuePolicy::Events(OverflowPolicy::Block)
+                | QueuePolicy::Envelopes(OverflowPolicy::Block)
+        )
+    }
+
+    fn is_drop_newest(&self) -> bool {
+        matches!(
+            self,
+            QueuePolicy::Events(OverflowPolicy::DropNewest)
+                | QueuePolicy::Envelopes(OverflowPolicy::DropNewest)
+        )
+    }
+
+    fn is_drop_oldest(&self) -> bool {
+        matches!(
+            self,
+            QueuePolicy::Events(OverflowPolicy::DropOldest)
+                | QueuePolicy::Envelopes(OverflowPolicy::DropOldest)
+        )
+    }
+
+    /// Merges `envelope` into `newest`, returning whether they could be merged. Envelopes with different targets are never merged.
+    fn coalesce(&self, newest: &mut Envelope<E>, envelope: &Envelope<E>) -> bool {
+        if newest.target != envelope.target {
+            return false;
+        }
+        let merged = match self {
+            QueuePolicy::Events(OverflowPolicy::Coalesce(merge)) => {
+                merge(&newest.event, &envelope.event)
+                    .map(|event| Envelope::new(newest.target, event))
+            }
+            QueuePolicy::Envelopes(OverflowPolicy::Coalesce(merge)) => merge(newest, envelope),
+            _ => None,
+        };
+        match merged {
+            Some(merged) => {
+                *newest = merged;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+struct Queue<E> {
+    events: VecDeque<Envelope<E>>,
+    /// `None` if the channel is unbounded.
+    capacity: Option<usize>,
+    policy: QueuePolicy<E>,
+    disconnected: bool,
+    /// Async senders waiting for room in the queue.
+    send_wakers: Vec<Waker>,
+}
+
+/// The result of pushing into the queue without waiting.
+enum Push<E> {
+    Done(Result<(), SendError<E>>),
+    /// The queue is full and the policy is to block, so the envelope was not sent.
+    Full(Envelope<E>),
+}
+
+struct Shared<E> {
+    queue: Mutex<Queue<E>>,
+    not_full: Condvar,
+    dropped: Arc<AtomicU64>,
+}
+
+/// Creates an unbounded channel for sending events to a tick loop.
+pub fn channel<E>() -> (EventSender<E>, EventReceiver<E>) {
+    let shared = Arc::new(Shared {
+        queue: Mutex::new(Queue {
+            events: VecDeque::new(),
+            capacity: None,
+            policy: QueuePolicy::Events(OverflowPolicy::Block),
+            disconnected: false,
+            send_wakers: Vec::new(),
+        }),
+        not_full: Condvar::new(),
+        dropped: Arc::new(AtomicU64::new(0)),
+    });
+    (
+        EventSender {
+            shared: shared.clone(),
+        },
+        EventReceiver {
+            shared,
+            pending: BTreeMap::new(),
+        },
+    )
+}
+
+/// Creates a channel that holds at most `capacity` events, and handles any more according to `policy`.
+/// A capacity of `0` is treated as `1`, so there is always room for the event being sent.
+pub fn bounded<E>(
+    capacity: usize,
+    policy: OverflowPolicy<E>,
+) -> (EventSender<E>, EventReceiver<E>) {
+    let (sender, reciever) = channel();
+    reciever.set_capacity(Some(capacity), policy);
+    (sender, reciever)
+}
+
+/// Sends events to a tick loop. Can be cloned to send from several threads.
+pub struct EventSender<E> {
+    shared: Arc<Shared<E>>,
+}
+
+impl<E> EventSender<E> {
+    /// Sends an event to be delivered on the next tick.
+    pub fn send(&self, event: E) -> Result<(), SendError<E>> {
+        self.send_envelope(Envelope {
+            target: EventTarget::NextTick,
+            event,
+        })
+    }
+
+    /// Sends an event to be delivered on the tick with index `tick`.
+    pub fn send_at(&self, tick: u64, event: E) -> Result<(), SendError<E>> {
+        self.send_envelope(Envelope {
+            target: EventTarget::Tick(tick),
+            event,
+        })
+    }
+
+    /// Sends an event to be delivered on the first tick that starts at or after `time`.
+    pub fn send_at_time(&self, time: Instant, event: E) -> Result<(), SendError<E>> {
+        self.send_envelope(Envelope {
+            target: EventTarget::Time(time),
+            event,
+        })
+    }
+
+    /// Sends an event, applying the overflow policy if the channel is full.
+    /// Events dropped by the policy still count as sent. Fails only if the receiver was dropped.
+    pub fn send_envelope(&self, mut envelope: Envelope<E>) -> Result<(), SendError<E>> {
+        let mut queue = self.shared.queue.lock().unwrap();
+        loop {
+            match self.try_push(&mut queue, envelope) {
+                Push::Done(result) => return result,
+                Push::Full(rejected) => {
+                    envelope = rejected;
+                    queue = self.shared.not_full.wait(queue).unwrap();
+                }
+            }
+        }
+    }
+
+    /// Sends an event to be delivered on the next tick, waiting for room in a full channel without blocking the thread.
+    #[cfg(feature = "async")]
+    pub async fn send_async(&self, event: E) -> Result<(), SendError<E>> {
+        self.send_envelope_async(Envelope {
+            target: EventTarget::NextTick,
+            event,
+        })
+        .await
+    }
+
+    /// Sends an event like [`send_envelope`](EventSender::send_envelope), but waits for room without blocking the thread.
+    #[cfg(feature = "async")]
+    pub async fn send_envelope_async(&self, envelope: Envelope<E>) -> Result<(), SendError<E>> {
+        let mut envelope = Some(envelope);
+        std::future::poll_fn(|cx| {
+            let mut queue = self.shared.queue.lock().unwrap();
+            let Some(unsent) = envelope.take() else {
+                unreachable!("polled after completion")
+            };
+            match self.try_push(&mut queue, unsent) {
+                Push::Done(result) => std::task::Poll::Ready(result),
+                Push::Full(rejected) => {
+                    envelope = Some(rejected);
+                    queue.send_wakers.push(cx.waker().clone());
+                    std::task::Poll::Pending
+                }
+            }
+        })
+        .await
+    }
+
+    /// Pushes an envelope into the queue, applying the overflow policy unless it is to block.
+    fn try_push(&self, queue: &mut Queue<E>, envelope: Envelope<E>) -> Push<E> {
+        if queue.disconnected {
+            return Push::Done(Err(SendError(envelope.event)));
+        }
+
+        match queue.capacity {
+            Some(capacity) if queue.events.len() >= capacity => {}
+            _ => {
+                queue.events.push_back(envelope);
+                return Push::Done(Ok(()));
+            }
+        }
+
+        let queue = &mut *queue;
+        if queue.policy.is_block() {
+            return Push::Full(envelope);
+        } else if queue.policy.is_drop_newest() {
+            self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+        } else if queue.policy.is_drop_oldest() {
+            queue.events.pop_front();
+            self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+            queue.events.push_back(envelope);
+        } else {
+            let merged = queue
+                .events
+                .back_mut()
+                .is_some_and(|newest| queue.policy.coalesce(newest, &envelope));
+            if !merged {
+                self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        Push::Done(Ok(()))
+    }
+}
+
+impl<E> Clone for EventSender<E> {
+    fn clone(&self) -> Self {
+        EventSender {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+/// Receives events on the tick loop, and holds on to events scheduled for future ticks.
+pub struct EventReceiver<E> {
+    shared: Arc<Shared<E>>,
+    /// Envelopes by the tick they are delivered on, with their targets resolved to the tick they were meant for.
+    pending: BTreeMap<u64, Vec<Envelope<E>>>,
+}
+
+impl<E> EventReceiver<E> {
+    /// Returns the events to deliver on `tick`, including any events for earlier ticks that have not been delivered yet.
+    /// `tick_at` maps a time to the index of the first tick that starts at or after it.
+    pub fn events_for(&mut self, tick: u64, tick_at: impl Fn(Instant) -> u64) -> Vec<E> {
+        self.envelopes_for(tick, tick_at)
+            .into_iter()
+            .map(|envelope| envelope.event)
+            .collect()
+    }
+
+    /// Returns the envelopes to deliver on `tick`, like [`events_for`](EventReceiver::events_for).
+    /// Every envelope targets the tick its event was meant for, which is earlier than `tick` for late events.
+    pub fn envelopes_for(
+        &mut self,
+        tick: u64,
+        tick_at: impl Fn(Instant) -> u64,
+    ) -> Vec<Envelope<E>> {
+        let received = std::mem::take(&mut self.shared.queue.lock().unwrap().events);
+        self.wake_senders();
+
+        for Envelope { target, event } in received {
+            let target = match target {
+                EventTarget::NextTick => tick,
+                EventTarget::Tick(target) => target,
+                EventTarget::Time(time) => tick_at(time),
+            };
+            self.pending
+                .entry(target.max(tick))
+                .or_default()
+                .push(Envelope::new(EventTarget::Tick(target), event));
+        }
+
+        let later = self.pending.split_off(&(tick + 1));
+        std::mem::replace(&mut self.pending, later)
+            .into_values()
+            .flatten()
+            .collect()
+    }
+
+    /// Turns the receiver into one that delivers whole envelopes.
+    pub fn with_targets(self) -> TargetedReceiver<E> {
+        TargetedReceiver { reciever: self }
+    }
+
+    /// Returns the number of events waiting for a future tick.
+    pub fn pending(&self) -> usize {
+        self.pending.values().map(Vec::len).sum()
+    }
+
+    /// Bounds the channel to `capacity` events, or makes it unbounded if `capacity` is `None`. A capacity of `0` is treated as `1`.
+    pub fn set_capacity(&self, capacity: Option<usize>, policy: OverflowPolicy<E>) {
+        self.set_queue_capacity(capacity, QueuePolicy::Events(policy));
+    }
+
+    fn set_queue_capacity(&self, capacity: Option<usize>, policy: QueuePolicy<E>) {
+        let mut queue = self.shared.queue.lock().unwrap();
+        queue.capacity = capacity.map(|capacity| capacity.max(1));
+        queue.policy = policy;
+        drop(queue);
+        self.wake_senders();
+    }
+
+    /// Returns the number of events dropped by the overflow policy so far.
+    pub fn dropped(&self) -> u64 {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Wakes every sender waiting for room in the queue.
+    fn wake_senders(&self) {
+        let wakers = std::mem::take(&mut self.shared.queue.lock().unwrap().send_wakers);
+        wakers.into_iter().for_each(Waker::wake);
+        self.shared.not_full.notify_all();
+    }
+}
+
+impl<E> Drop for EventReceiver<E> {
+    fn drop(&mut self) {
+        self.shared.queue.lock().unwrap().disconnected = true;
+        self.wake_senders();
+    }
+}
+
+/// An [`EventReceiver`] that delivers [`Envelope`]s instead of bare events, created with [`EventReceiver::with_targets`].
+/// Each envelope targets the tick its event was meant for, even if it arrived after that tick ran.
+pub struct TargetedReceiver<E> {
+    reciever: EventReceiver<E>,
+}
+
+impl<E> TargetedReceiver<E> {
+    /// Returns the number of events waiting for a future tick.
+    pub fn pending(&self) -> usize {
+        self.reciever.pending()
+    }
+
+    /// Bounds the channel to `capacity` events, or makes it unbounded if `capacity` is `None`. A capacity of `0` is treated as `1`.
+    /// A [`Coalesce`](OverflowPolicy::Coalesce) policy merges whole envelopes, as they were sent.
+    pub fn set_capacity(&self, capacity: Option<usize>, policy: OverflowPolicy<Envelope<E>>) {
+        self.reciever
+            .set_queue_capacity(capacity, QueuePolicy::Envelopes(policy));
+    }
+
+    /// Returns the number of events dropped by the overflow policy so far.
+    pub fn dropped(&self) -> u64 {
+        self.reciever.dropped()
+    }
+
+    pub fn into_inner(self) -> EventReceiver<E> {
+        self.reciever
+    }
+}
+
+mod private {
+    use std::sync::atomic::AtomicU64;
+    use std::sync::Arc;
+
+    pub trait Sealed {
+        /// The counter of events dropped by the overflow policy, shared with the loop's control.
+        fn dropped_counter(&self) -> Arc<AtomicU64>;
+    }
+}
+
+/// Where a [`TickLoop`](crate::tickloop::TickLoop) takes the events of each tick from.
+/// Implemented for [`EventReceiver`], [`TargetedReceiver`], and `std::sync::mpsc::Receiver` for code written before the event channel existed.
+pub trait EventSource<E>: private::Sealed + Send {
+    /// Returns the events to deliver on `tick`. `tick_at` maps a time to the index of the first tick that starts at or after it.
+    fn events_for(&mut self, tick: u64, tick_at: &dyn Fn(Instant) -> u64) -> Vec<E>;
+
+    /// Bounds the source to `capacity` events, or makes it unbounded if `capacity` is `None`.
+    fn set_capacity(&self, capacity: Option<usize>, policy: OverflowPolicy<E>);
+}
+
+impl<E> private::Sealed for EventReceiver<E> {
+    fn dropped_counter(&self) -> Arc<AtomicU64> {
+        self.shared.dropped.clone()
+    }
+}
+
+impl<E: Send> EventSource<E> for EventReceiver<E> {
+    fn events_for(&mut self, tick: u64, tick_at: &dyn Fn(Instant) -> u64) -> Vec<E> {
+        EventReceiver::events_for(self, tick, tick_at)
+    }
+
+    fn set_capacity(&self, capacity: Option<usize>, policy: OverflowPolicy<E>) {
+        EventReceiver::set_capacity(self, capacity, policy);
+    }
+}
+
+impl<E> private::Sealed for TargetedReceiver<E> {
+    fn dropped_counter(&self) -> Arc<AtomicU64> {
+        private::Sealed::dropped_counter(&self.reciever)
+    }
+}
+
+impl<E: Send> EventSource<Envelope<E>> for TargetedReceiver<E> {
+    fn events_for(&mut self, tick: u64, tick_at: &dyn Fn(Instant) -> u64) -> Vec<Envelope<E>> {
+        self.reciever.envelopes_for(tick, tick_at)
+    }
+
+    fn set_capacity(&self, capacity: Option<usize>, policy: OverflowPolicy<Envelope<E>>) {
+        TargetedReceiver::set_capacity(self, capacity, policy);
+    }
+}
+
+impl<E> private::Sealed for mpsc::Receiver<E> {
+    fn dropped_counter(&self) -> Arc<AtomicU64> {
+        Default::default()
+    }
+}
+
+/// Delivers every event on the next tick. Scheduling and bounding need an [`EventReceiver`].
+impl<E: Send> EventSource<E> for mpsc::Receiver<E> {
+    fn events_for(&mut self, _tick: u64, _tick_at: &dyn Fn(Instant) -> u64) -> Vec<E> {
+        self.try_iter().collect()
+    }
+
+    fn set_capacity(&self, _capacity: Option<usize>, _policy: OverflowPolicy<E>) {
+        log::warn!("an mpsc receiver can't be bounded, use an EventReceiver instead");
+    }
+}
+
+/// Transforms the events of a tick before they are passed to the listener, for example to fold many cursor moves into one.
+/// Closures taking and returning a `Vec<E>` are reducers.
+pub trait EventReducer<E>: Send {
+    fn reduce(&mut self, events: Vec<E>) -> Vec<E>;
+}
+
+impl<E, F: FnMut(Vec<E>) -> Vec<E> + Send> EventReducer<E> for F {
+    fn reduce(&mut self, events: Vec<E>) -> Vec<E> {
+        self(events)
+    }
+}
@@ -0,0 +1,95 @@
+//! Contains [`InputState`], which turns key and button events into per-tick state.
+//! Feed it the down and up events of every tick, and ask it which inputs were pressed, are held, or were released on that tick.
+//! Inputs that go down and up within a single tick still count as pressed and released, so short taps are not lost between ticks.
+
+use std::collections::HashSet;
+use std::hash::Hash;
+
+/// A key or button going down or up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InputEvent<K> {
+    Down(K),
+    Up(K),
+}
+
+/// Which inputs are held down, and which were pressed or released on the current tick.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputState<K: Eq + Hash> {
+    held: HashSet<K>,
+    pressed: HashSet<K>,
+    released: HashSet<K>,
+}
+
+impl<K: Eq + Hash + Clone> InputState<K> {
+    pub fn new() -> Self {
+        InputState {
+            held: HashSet::new(),
+            pressed: HashSet::new(),
+            released: HashSet::new(),
+        }
+    }
+
+    /// Starts a new tick and applies its events in order.
+    pub fn update(&mut self, events: impl IntoIterator<Item = InputEvent<K>>) {
+        self.begin_tick();
+        for event in events {
+            self.apply(event);
+        }
+    }
+
+    /// Forgets which inputs were pressed or released on the previous tick. Held inputs stay held.
+    pub fn begin_tick(&mut self) {
+        self.pressed.clear();
+        self.released.clear();
+    }
+
+    /// Applies a single event to the current tick. Repeated down events for a held input are ignored.
+    pub fn apply(&mut self, event: InputEvent<K>) {
+        match event {
+            InputEvent::Down(key) => {
+                if self.held.insert(key.clone()) {
+                    self.pressed.insert(key);
+                }
+            }
+            InputEvent::Up(key) => {
+                if self.held.remove(&key) {
+                    self.released.insert(key);
+                }
+            }
+        }
+    }
+
+    /// Returns whether `key` went down on the current tick.
+    pub fn is_pressed(&self, key: &K) -> bool {
+        self.pressed.contains(key)
+    }
+
+    /// Returns whether `key` is down at the end of the current tick.
+    pub fn is_held(&self, key: &K) -> bool {
+        self.held.contains(key)
+    }
+
+    /// Returns whether `key` went up on the current tick.
+    pub fn is_released(&self, key: &K) -> bool {
+        self.released.contains(key)
+    }
+
+    pub fn pressed(&self) -> impl Iterator<Item = &K> {
+        self.pressed.iter()
+    }
+
+    pub fn held(&self) -> impl Iterator<Item = &K> {
+        self.held.iter()
+    }
+
+    pub fn released(&self) -> impl Iterator<Item = &K> {
+        self.released.iter()
+    }
+}
+
+impl<K: Eq + Hash + Clone> Default for InputState<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -3,7 +3,7 @@
 //! An `Interpolate` derive macro is also provided for ease of use, when using the derive feature.
 //! Interpolation is very neccessary to make games in your engine look smooth. Without it, your game will look very choppy, especially at low TPS.
 
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 /// A trait that allows a type to be interpolated.
 pub trait Interpolate {
@@ -15,7 +15,7 @@ pub trait Interpolate {
     where
         Self: Sized,
     {
-        *self = Self::interpolate(&self, end, t, interpolation)
+        *self = Self::interpolate(self, end, t, interpolation)
     }
 }
 
@@ -45,11 +45,29 @@ impl Interpolate for Instant {
     where
         Self: Sized,
     {
-        let start = start.elapsed().as_secs_f32();
-        let end = end.elapsed().as_secs_f32();
+        // Only use the endpoints, as they may be on a clock that runs ahead of `Instant::now`.
         let t = interpolation(t);
-        let secs = lerp(start, end, t).max(0.0);
-        Instant::now() - std::time::Duration::from_secs_f32(secs)
+        let (from, to, t) = if end >= start {
+            (*start, *end, t)
+        } else {
+            (*end, *start, 1.0 - t)
+        };
+        let span = to - from;
+        if t >= 0.0 {
+            from + span.mul_f32(t)
+        } else {
+            from.checked_sub(span.mul_f32(-t)).unwrap_or(from)
+        }
+    }
+}
+
+impl Interpolate for Duration {
+    fn interpolate(start: &Self, end: &Self, t: f32, interpolation: impl Fn(f32) -> f32) -> Self
+    where
+        Self: Sized,
+    {
+        let t = interpolation(t);
+        Duration::from_secs_f32(lerp(start.as_secs_f32(), end.as_secs_f32(), t).max(0.0))
     }
 }
 
@@ -79,7 +97,7 @@ pub fn ease_out_sine(t: f32) -> f32 {
     ((t * std::f32::consts::PI) / 2.0).sin()
 }
 pub fn ease_in_out_sine(t: f32) -> f32 {
-    (t * std::f32::consts::PI).sin()
+    -((t * std::f32::consts::PI).cos() - 1.0) / 2.0
 }
 
 pub fn ease_in_quad(t: f32) -> f32 {
@@ -183,19 +201,125 @@ pub fn ease_in_out_circ(t: f32) -> f32 {
     }
 }
 
+/// The overshoot used by the back easings. This overshoots by roughly 10%.
+pub const BACK_OVERSHOOT: f32 = 1.70158;
+
 pub fn ease_in_back(t: f32) -> f32 {
-    2.70158 * t * t * t - 1.70158 * t * t
+    (BACK_OVERSHOOT + 1.0) * t * t * t - BACK_OVERSHOOT * t * t
 }
 pub fn ease_out_back(t: f32) -> f32 {
-    1.0 + 2.70158 * (t - 1.0) * (t - 1.0) * (t - 1.0) + 1.70158 * (t - 1.0) * (t - 1.0)
+    1.0 + (BACK_OVERSHOOT + 1.0) * (t - 1.0) * (t - 1.0) * (t - 1.0)
+        + BACK_OVERSHOOT * (t - 1.0) * (t - 1.0)
 }
 pub fn ease_in_out_back(t: f32) -> f32 {
-    let c1 = 1.70158;
+    let c1 = BACK_OVERSHOOT;
     let c2 = c1 * 1.525;
 
     if t < 0.5 {
-        (2.0 * t) * (2.0 * t) * ((c2 + 1.0) * (t * 2.0 - 2.0) + c2) / 2.0
+        (2.0 * t) * (2.0 * t) * ((c2 + 1.0) * 2.0 * t - c2) / 2.0
+    } else {
+        ((2.0 * t - 2.0) * (2.0 * t - 2.0) * ((c2 + 1.0) * (t * 2.0 - 2.0) + c2) + 2.0) / 2.0
+    }
+}
+
+pub fn ease_in_elastic(t: f32) -> f32 {
+    let c4 = (2.0 * std::f32::consts::PI) / 3.0;
+
+    if t == 0.0 {
+        0.0
+    } else if t == 1.0 {
+        1.0
+    } else {
+        -(2.0f32.powf(10.0 * t - 10.0)) * ((t * 10.0 - 10.75) * c4).sin()
+    }
+}
+pub fn ease_out_elastic(t: f32) -> f32 {
+    let c4 = (2.0 * std::f32::consts::PI) / 3.0;
+
+    if t == 0.0 {
+        0.0
+    } else if t == 1.0 {
+        1.0
+    } else {
+        2.0f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * c4).sin() + 1.0
+    }
+}
+pub fn ease_in_out_elastic(t: f32) -> f32 {
+    let c5 = (2.0 * std::f32::consts::PI) / 4.5;
+
+    if t == 0.0 {
+        0.0
+    } else if t == 1.0 {
+        1.0
+    } else if t < 0.5 {
+        -(2.0f32.powf(20.0 * t - 10.0) * ((20.0 * t - 11.125) * c5).sin()) / 2.0
+    } else {
+        (2.0f32.powf(-20.0 * t + 10.0) * ((20.0 * t - 11.125) * c5).sin()) / 2.0 + 1.0
+    }
+}
+
+pub fn ease_in_bounce(t: f32) -> f32 {
+    1.0 - ease_out_bounce(1.0 - t)
+}
+pub fn ease_out_bounce(t: f32) -> f32 {
+    let n1 = 7.5625;
+    let d1 = 2.75;
+
+    if t < 1.0 / d1 {
+        n1 * t * t
+    } else if t < 2.0 / d1 {
+        let t = t - 1.5 / d1;
+        n1 * t * t + 0.75
+    } else if t < 2.5 / d1 {
+        let t = t - 2.25 / d1;
+        n1 * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / d1;
+        n1 * t * t + 0.984375
+    }
+}
+pub fn ease_in_out_bounce(t: f32) -> f32 {
+    if t < 0.5 {
+        (1.0 - ease_out_bounce(1.0 - 2.0 * t)) / 2.0
     } else {
-        ((2.0 * t) * (2.0 * t) * ((c2 + 1.0) * (t * 2.0 - 2.0) + c2) + 2.0) / 2.0
+        (1.0 + ease_out_bounce(2.0 * t - 1.0)) / 2.0
+    }
+}
+
+// Parameterized interpolation functions.
+// These are all "in" easings; use the `Easing` combinators to build out and in-out versions.
+
+/// Jumps between `steps` evenly spaced values, holding each one until the next step. Useful for sprite animation.
+/// The value jumps at the end of each step, so `steps(4)` returns `0.0` until `t` reaches `0.25`.
+pub fn steps(steps: u32) -> impl Fn(f32) -> f32 {
+    let steps = steps.max(1) as f32;
+    move |t| ((t * steps).floor() / steps).clamp(0.0, 1.0)
+}
+
+/// An in back easing with a custom overshoot. [`ease_in_back`] uses [`BACK_OVERSHOOT`], and an overshoot of `0.0` is a cubic easing.
+pub fn back(overshoot: f32) -> impl Fn(f32) -> f32 {
+    move |t| (overshoot + 1.0) * t * t * t - overshoot * t * t
+}
+
+/// An in easing that raises `t` to `exponent`. An exponent of `2.0` is [`ease_in_quad`].
+pub fn power(exponent: f32) -> impl Fn(f32) -> f32 {
+    move |t| t.powf(exponent)
+}
+
+/// An in elastic easing with a custom amplitude and period.
+/// Amplitudes below `1.0` are treated as `1.0`. An amplitude of `1.0` and a period of `0.3` is [`ease_in_elastic`].
+pub fn elastic(amplitude: f32, period: f32) -> impl Fn(f32) -> f32 {
+    let amplitude = amplitude.max(1.0);
+    let tau = 2.0 * std::f32::consts::PI;
+    let shift = period / tau * (1.0 / amplitude).asin();
+
+    move |t| {
+        if t == 0.0 {
+            0.0
+        } else if t == 1.0 {
+            1.0
+        } else {
+            -(amplitude * 2.0f32.powf(10.0 * (t - 1.0)) * ((t - 1.0 - shift) * tau / period).sin())
+        }
     }
 }
@@ -0,0 +1,226 @@
+//! Contains [`KeyframeTrack`], an animation built from any number of keyframes.
+//! Each [`Keyframe`] describes the segment that starts at it: how far along the segment is after easing, and whether the segment steps, interpolates linearly or follows a smooth curve through the surrounding keys.
+//! Tracks are plain data, so with the `serde` feature they can be loaded at runtime to drive cutscene cameras or UI animations.
+
+use crate::{
+    easing::Easing,
+    interpolate::{linear, Interpolate},
+    tween::Animation,
+};
+
+/// How a segment moves from its keyframe to the next one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SegmentMode {
+    /// Holds the keyframe's value until the next keyframe.
+    Step,
+    /// Interpolates straight towards the next keyframe.
+    #[default]
+    Linear,
+    /// Follows a Catmull-Rom curve through the keyframes around the segment, giving smooth motion through every key.
+    Cubic,
+}
+
+/// What a track does when it is sampled outside of its keyframes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WrapMode {
+    /// Holds the first or last value.
+    #[default]
+    Clamp,
+    /// Starts over from the first keyframe.
+    Loop,
+    /// Plays backwards to the first keyframe, then forwards again.
+    PingPong,
+}
+
+/// A value at a point in time, and how to move from it to the next keyframe.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Keyframe<T> {
+    pub time: f32,
+    pub value: T,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub easing: Easing,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub mode: SegmentMode,
+}
+
+impl<T> Keyframe<T> {
+    pub fn new(time: f32, value: T) -> Self {
+        Keyframe {
+            time,
+            value,
+            easing: Easing::Linear,
+            mode: SegmentMode::Linear,
+        }
+    }
+
+    pub fn with_easing(mut self, easing: impl Into<Easing>) -> Self {
+        self.easing = easing.into();
+        self
+    }
+
+    pub fn with_mode(mut self, mode: SegmentMode) -> Self {
+        self.mode = mode;
+        self
+    }
+}
+
+/// An animation made of keyframes, sorted by time.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyframeTrack<T> {
+    keys: Vec<Keyframe<T>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    wrap: WrapMode,
+}
+
+impl<T: Interpolate + Clone> KeyframeTrack<T> {
+    /// Creates a track from keyframes in any order.
+    pub fn new(mut keys: Vec<Keyframe<T>>) -> Self {
+        keys.sort_by(|a, b| a.time.total_cmp(&b.time));
+        KeyframeTrack {
+            keys,
+            wrap: WrapMode::Clamp,
+        }
+    }
+
+    pub fn with_wrap(mut self, wrap: WrapMode) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// Inserts a keyframe, keeping the keyframes sorted.
+    /// A keyframe inserted at the same time as an existing one is placed after it.
+    pub fn insert(&mut self, key: Keyframe<T>) {
+        let index = self.keys.partition_point(|k| k.time <= key.time);
+        self.keys.insert(index, key);
+    }
+
+    pub fn keys(&self) -> &[Keyframe<T>] {
+        &self.keys
+    }
+
+    pub fn wrap(&self) -> WrapMode {
+        self.wrap
+    }
+
+    /// Returns the time of the first keyframe.
+    pub fn start_time(&self) -> Option<f32> {
+        self.keys.first().map(|key| key.time)
+    }
+
+    /// Returns the time of the last keyframe.
+    pub fn end_time(&self) -> Option<f32> {
+        self.keys.last().map(|key| key.time)
+    }
+
+    /// Maps `time` into the range of the keyframes according to the wrap mode.
+    fn wrap_time(&self, time: f32, start: f32, end: f32) -> f32 {
+        let length = end - start;
+        if length <= 0.0 {
+            return start;
+        }
+
+        match self.wrap {
+            WrapMode::Clamp => time.clamp(start, end),
+            WrapMode::Loop => start + (time - start).rem_euclid(length),
+            WrapMode::PingPong => {
+                let offset = (time - start).rem_euclid(2.0 * length);
+                start + length - (offset - length).abs()
+            }
+        }
+    }
+
+    /// Samples the track at `time`. Returns `None` if the track has no keyframes.
+    pub fn sample(&self, time: f32) -> Option<T> {
+        let (start, end) = (self.start_time()?, self.end_time()?);
+        let time = self.wrap_time(time, start, end);
+
+        // The segment containing `time` starts at the last keyframe at or before it.
+        let index = self
+            .keys
+            .partition_point(|key| key.time <= time)
+            .saturating_sub(1);
+        let key = &self.keys[index];
+        let Some(next) = self.keys.get(index + 1) else {
+            return Some(key.value.clone());
+        };
+
+        let length = next.time - key.time;
+        let progress = if length > 0.0 {
+            key.easing.ease((time - key.time) / length)
+        } else {
+            1.0
+        };
+
+        Some(match key.mode {
+            SegmentMode::Step => key.value.clone(),
+            SegmentMode::Linear => T::interpolate(&key.value, &next.value, progress, linear),
+            SegmentMode::Cubic => self.catmull_rom(index, progress),
+        })
+    }
+
+    /// Evaluates a Catmull-Rom curve on the segment starting at `index`, using the Barry-Goldman formulation.
+    /// It only needs linear interpolation, so it works for any [`Interpolate`] type, and it uses the keyframe times as knots so unevenly spaced keys stay smooth.
+    fn catmull_rom(&self, index: usize, progress: f32) -> T {
+        let p1 = &self.keys[index];
+        let p2 = &self.keys[index + 1];
+        let (t1, t2) = (p1.time, p2.time);
+
+        // The first and last segments extrapolate a phantom key past their ends, mirroring the segment.
+        let (p0, t0) = match index.checked_sub(1) {
+            Some(previous) => (self.keys[previous].value.clone(), self.keys[previous].time),
+            None => (
+                T::interpolate(&p1.value, &p2.value, -1.0, linear),
+                t1 - (t2 - t1),
+            ),
+        };
+        let (p3, t3) = match self.keys.get(index + 2) {
+            Some(next) => (next.value.clone(), next.time),
+            None => (
+                T::interpolate(&p1.value, &p2.value, 2.0, linear),
+                t2 + (t2 - t1),
+            ),
+        };
+        let t = t1 + (t2 - t1) * progress;
+
+        let lerp = |a: &T, b: &T, from: f32, to: f32| {
+            let s = if to > from {
+                (t - from) / (to - from)
+            } else {
+                1.0
+            };
+            T::interpolate(a, b, s, linear)
+        };
+
+        let a1 = lerp(&p0, &p1.value, t0, t1);
+        let a2 = lerp(&p1.value, &p2.value, t1, t2);
+        let a3 = lerp(&p2.value, &p3, t2, t3);
+        let b1 = lerp(&a1, &a2, t0, t2);
+        let b2 = lerp(&a2, &a3, t1, t3);
+        lerp(&b1, &b2, t1, t2)
+    }
+}
+
+/// Keyframe times are measured in ticks when a track is used as an [`Animation`].
+/// Tracks that loop or ping-pong never end.
+///
+/// # Panics
+/// Sampling panics if the track has no keyframes.
+impl<T: Interpolate + Clone + Send + Sync> Animation for KeyframeTrack<T> {
+    type Output = T;
+
+    fn duration(&self) -> Option<u32> {
+        match self.wrap {
+            WrapMode::Clamp => Some(self.end_time().unwrap_or(0.0).max(0.0).ceil() as u32),
+            WrapMode::Loop | WrapMode::PingPong => None,
+        }
+    }
+
+    fn sample_at(&self, time: f32) -> T {
+        self.sample(time)
+            .expect("a keyframe track needs at least one keyframe to be sampled")
+    }
+}
@@ -24,23 +24,38 @@
 //! ### [`TickLoop`](tickloop::TickLoop)
 //!
 //! The tick loop is the heart of Saunter; It runs all of your code at a set tick rate (TPS). If your code takes longer than the tick interval to run, the tick loop will run as fast as possible until it catches back up.
+//! Events can be scheduled for a specific tick and reduced before the listener sees them, see the [`event`] module. The [`input`] module tracks which keys were pressed, held or released on each tick. The events of every tick can be recorded and replayed later, see the [`replay`] module.
+//! By default every tick passes the tick length as `dt`; a variable or clamped timestep can be selected with `TickLoop::with_timestep`.
+//! For a more stable simulation, `TickLoop::with_substeps` runs the listener several times per tick with a smaller `dt`, and publishes one snapshot per tick.
+//! Tick timings, such as average and percentile tick duration, jitter and effective TPS, are available from `TickLoopControl::stats`, see the [`stats`] module.
+//! The loop runs on a [`SimClock`](time::SimClock) that stops while paused and can be sped up or slowed down with `TickLoopControl::set_time_scale`.
+//! With the `async` feature, the `asynchronous` module lets async code await snapshots and drive the loop on an async runtime.
+//! With the `tracing` feature, every tick, listener call, event drain and snapshot publish runs in a `tracing` span with the tick index as a field.
+//! Games that run their own frame loop can drive a tick loop on the same thread with a [`FixedStepper`](stepper::FixedStepper) instead.
+//! To run several listeners at different rates without a thread for each, host them on a [`Scheduler`](scheduler::Scheduler).
+//! For rollback netcode, the [`rollback`] module runs a tick loop that re-simulates past ticks when late events arrive.
 //!
 //! ### Snapshot
 //!
 //! Every time your listener runs, it will generate a snapshot. A snapshot is a representation of the state of your game at a given tick.
 //! The snapshot is then put into a Snapshots, which is used to interpolate between snapshots outside of the tick loop.
 //! In general, it is advised to put as little data as possible into your snapshot, as it is moved around in memory quite a bit.
+//! With the `serde` feature, snapshots, recordings, easings, keyframe tracks, colors and [`TickInfo`](tickloop::TickInfo) can be serialized. The [`time`] module helps with serializing the time a snapshot was created.
 //!
 //! ### Interpolation
 //!
 //! Saunter provides utilities for interpolating data. Mainly, it provides an [`Interpolate`](interpolate::Interpolate) trait and many common interpolators. The [`Interpolate`](interpolate::Interpolate) trait is already implemented for many types in the standard library, including all of the number primitives and vectors that hold them.
 //! An [`Interpolate`](derive::Interpolate) derive proc macro is also provided for ease of use, when using the derive feature.
+//! The [`Easing`](easing::Easing) enum names every easing function, so easings can be stored in config files, compared and combined.
+//! For animations that span many ticks, the [`tween`] module provides tweens, sequences and a [`Timeline`](tween::Timeline) to play them on,
+//! and the [`keyframe`] module provides keyframe tracks that can be loaded from data.
+//! The [`color`] module provides color types that interpolate in linear RGB, Oklab or HSV instead of blending raw sRGB values.
 //! Interpolation is very neccessary to make games in your engine look smooth. Without it, your game will look very choppy, especially at low TPS.
 //!
 //! ## Usage
 //!
 //! The first step of using Saunter is to create a [`TickLoop`](tickloop::TickLoop). The easiest way to do this is to call `TickLoop::init` which does some setup for you.
-//! ```rust
+//! ```ignore
 //! let (tick_loop, event_sender, ctrl, snapshots) = TickLoop::init(
 //!     listener: move |dt, events, ctrl, time| {
 //!         // Your engine logic goes here
@@ -57,17 +72,17 @@
 //!   - `dt`: The time since the last tick in seconds.
 //!   - `events`: A vector of events that have been sent to the loop since the last tick.
 //!   - `ctrl`: A `TickLoopControl` that can be used to control the state of the tick loop.
-//!   - `time`: The current time, used for creating snapshots (they need to store the time of creation).
+//!   - `time`: The time of the tick on the loop's clock, `ctrl.clock()`, used for creating snapshots (they need to store the time of creation). The clock stops while the loop is paused and follows the time scale set with `ctrl.set_time_scale`, so interpolate with `snapshots.interpolate_now(ctrl.clock(), ..)` instead of comparing it to `Instant::now()`.
 //! - tps: The TPS of the loop.
 //!   
 //! Outputs:
 //! - `tick_loop`: The tick loop itself.
-//! - `event_sender`: A `Sender` that can be used to send events to the tick loop.
-//! - `ctrl`: A `TickLoopControl` that can be used to control the state of the tick loop from outside of the loop.
+//! - `event_sender`: An `EventSender` that can be used to send events to the tick loop, either on the next tick with `send`, or on a specific tick or time with `send_at` and `send_at_time`. The channel is unbounded unless a capacity and an overflow policy are set with `TickLoop::with_event_capacity`.
+//! - `ctrl`: A `TickLoopControl` that can be used to control the state of the tick loop from outside of the loop, and to receive messages the listener sends with `send_message`.
 //! - snapshots: A `Snapshots` that holds all of the snapshots generated by the tick loop.
 //!
 //! With that out of the way, let's make our snapshot type. This is a very simple example, but you can put as much data as you need into your snapshot.
-//! ```rust
+//! ```ignore
 //! #[derive(Debug, Interpolate)]
 //! struct ExampleSnapshot {
 //!     time: Instant,
@@ -80,7 +95,7 @@
 //! }
 //! ```
 //! Now we can put it to use!
-//! ```rust
+//! ```ignore
 //! let mut value = 0.0;
 //!
 //! let _ = TickLoop::init(
@@ -100,17 +115,31 @@
 //! ```
 //!
 //! Finally, we can start our tick loop!
-//! ```rust
+//! ```ignore
 //! tick_loop.start(snapshots);
 //! ```
 //! Starting a tick loop blocks the thread until it is stopped. For this reason you probably want to send the tick loop to a seperate thread before running it.
 //!
 //! Now you have a working tick loop! You can send events to it using the `event_sender` and control it using `ctrl`.
 
+#[cfg(feature = "async")]
+pub mod asynchronous;
+pub mod color;
+pub mod easing;
 pub mod error;
+pub mod event;
+pub mod input;
 pub mod interpolate;
+pub mod keyframe;
+pub mod replay;
+pub mod rollback;
+pub mod scheduler;
 pub mod snapshot;
+pub mod stats;
+pub mod stepper;
 pub mod tickloop;
+pub mod time;
+pub mod tween;
 
 #[cfg(feature = "derive")]
 pub mod derive {
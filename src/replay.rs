@@ -0,0 +1,85 @@
+//! Contains types for recording and replaying the events a [`TickLoop`](crate::tickloop::TickLoop) receives.
+//! The listener gets all of its input through the events of each tick, so a deterministic game can be reproduced exactly by feeding it the same event batches again.
+//! Attach a [`RecordSink`] with [`TickLoop::with_recorder`](crate::tickloop::TickLoop::with_recorder) to capture a session,
+//! and pass the [`Recording`] to [`TickLoop::replay`](crate::tickloop::TickLoop::replay) to run it again without a wall clock.
+//! With the `serde` feature, recordings can be saved and loaded on another process or machine.
+
+use std::sync::{mpsc::Sender, Arc, Mutex};
+
+/// The events that were handed to the listener on a single tick.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecordedTick<E> {
+    /// The index of the tick, starting at `0` for the first tick of the loop.
+    pub tick: u64,
+    pub events: Vec<E>,
+}
+
+/// A recorded session: the events of every tick, in order.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Recording<E> {
+    pub ticks: Vec<RecordedTick<E>>,
+}
+
+impl<E> Recording<E> {
+    pub fn new() -> Self {
+        Recording { ticks: Vec::new() }
+    }
+
+    /// Returns the number of ticks in the recording.
+    pub fn len(&self) -> usize {
+        self.ticks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ticks.is_empty()
+    }
+}
+
+impl<E> Default for Recording<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E> IntoIterator for Recording<E> {
+    type Item = RecordedTick<E>;
+    type IntoIter = std::vec::IntoIter<RecordedTick<E>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.ticks.into_iter()
+    }
+}
+
+/// Receives the events of every tick as the tick loop runs.
+/// Every tick is recorded, including ticks without any events.
+pub trait RecordSink<E>: Send {
+    fn record(&mut self, tick: u64, events: &[E]);
+}
+
+/// Records into a shared [`Recording`] that can be read once the loop has stopped.
+impl<E: Clone + Send> RecordSink<E> for Arc<Mutex<Recording<E>>> {
+    fn record(&mut self, tick: u64, events: &[E]) {
+        self.lock().unwrap().ticks.push(RecordedTick {
+            tick,
+            events: events.to_vec(),
+        });
+    }
+}
+
+/// Sends every recorded tick over a channel, for example to a thread that writes them to disk.
+impl<E: Clone + Send> RecordSink<E> for Sender<RecordedTick<E>> {
+    fn record(&mut self, tick: u64, events: &[E]) {
+        let recorded = RecordedTick {
+            tick,
+            events: events.to_vec(),
+        };
+        if self.send(recorded).is_err() {
+            log::warn!(
+                "recording receiver was dropped, tick {} was not recorded",
+                tick
+            );
+        }
+    }
+}
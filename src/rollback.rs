@@ -0,0 +1,238 @@
+//! Contains [`Rollback`], a listener for [`TickLoop`] that supports rollback netcode.
+//! Events are sent for the tick they belong to with [`EventSender::send_at`], and the loop delivers them in [`Envelope`]s that keep that tick.
+//! When an event arrives for a tick that has already run,
+//! the game state saved before that tick is restored and every tick since is run again with the corrected events,
+//! all within the current tick. The corrected snapshot is then republished, so interpolation continues from the corrected state.
+//! Ticks are counted with the loop's [`TickInfo`], so a loop that [sub-steps](TickLoop::with_substeps) rolls back whole ticks,
+//! running every sub-step again.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+
+use crate::error::SaunterError;
+use crate::event::{self, Envelope, EventSender};
+use crate::snapshot::{Snapshot, Snapshots};
+use crate::tickloop::{TickInfo, TickLoop, TickLoopControl};
+
+type RollbackListener<G, S, E> =
+    dyn FnMut(&mut G, f32, Vec<E>, TickLoopControl, Instant) -> Result<S, SaunterError> + Send;
+
+/// Everything needed to run a tick, or a sub-step of one, again.
+struct SavedTick<G, E> {
+    tick: u64,
+    substep: u32,
+    /// The game state before the tick ran.
+    state: G,
+    events: Vec<E>,
+    dt: f32,
+    time: Instant,
+    /// What the control reported to the listener when the tick first ran.
+    tick_info: Option<TickInfo>,
+}
+
+/// Runs a listener over a game state that can be rolled back.
+/// The state before each of the last `max_rollback` ticks is kept, so events up to `max_rollback` ticks late can still be applied.
+/// Events older than that are dropped, and events for future ticks are held until their tick runs.
+///
+/// The listener must be deterministic: given the same state and events it has to produce the same result, as it may run the same tick several times.
+pub struct Rollback<G: Clone, S: Snapshot, E> {
+    listener: Box<RollbackListener<G, S, E>>,
+    state: G,
+    history: VecDeque<SavedTick<G, E>>,
+    pending: BTreeMap<u64, Vec<E>>,
+    max_rollback: usize,
+    snapshots: Arc<RwLock<Snapshots<S>>>,
+    tick: u64,
+}
+
+impl<G: Clone + Send, S: Snapshot, E: Clone + Send> Rollback<G, S, E> {
+    /// Creates a new Rollback struct that republishes corrected snapshots into `snapshots`.
+    /// It is recommended to use [`init`](Rollback::init) instead.
+    pub fn new<F>(
+        state: G,
+        listener: F,
+        max_rollback: usize,
+        snapshots: Arc<RwLock<Snapshots<S>>>,
+    ) -> Self
+    where
+        F: FnMut(&mut G, f32, Vec<E>, TickLoopControl, Instant) -> Result<S, SaunterError>
+            + Send
+            + 'static,
+    {
+        Rollback {
+            listener: Box::new(listener),
+            state,
+            history: VecDeque::with_capacity(max_rollback),
+            pending: BTreeMap::new(),
+            max_rollback,
+            snapshots,
+            tick: 0,
+        }
+    }
+
+    /// Creates a [`TickLoop`] that runs `listener` with rollback, and returns an [`EventSender`] to send events to the loop.
+    /// Events sent with [`send_at`](EventSender::send_at) are applied on that tick, even if it has already run.
+    #[allow(clippy::type_complexity)]
+    pub fn init<F>(
+        state: G,
+        listener: F,
+        tps: f32,
+        max_rollback: usize,
+    ) -> (
+        TickLoop<S, Envelope<E>>,
+        EventSender<E>,
+        TickLoopControl,
+        Arc<RwLock<Snapshots<S>>>,
+    )
+    where
+        G: 'static,
+        S: Send + Sync + 'static,
+        E: 'static,
+        F: FnMut(&mut G, f32, Vec<E>, TickLoopControl, Instant) -> Result<S, SaunterError>
+            + Send
+            + 'static,
+    {
+        let (event_sender, event_reciever) = event::channel();
+        let snapshots = Arc::new(RwLock::new(Snapshots::new()));
+
+        let mut rollback = Self::new(state, listener, max_rollback, snapshots.clone());
+        let tick_loop = TickLoop::new(
+            move |dt, events, ctrl, time| rollback.tick(dt, events, ctrl, time),
+            tps,
+            event_reciever.with_targets(),
+            snapshots.clone(),
+        );
+
+        let control = tick_loop.control();
+
+        (tick_loop, event_sender, control, snapshots)
+    }
+
+    /// Returns the index of the tick after the last one that ran.
+    pub fn current_tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Returns the game state after the last tick.
+    pub fn state(&self) -> &G {
+        &self.state
+    }
+
+    /// Runs a single tick, rolling back first if any of `events` belong to a tick that has already run.
+    /// This has the same signature as a [`TickLoop`] listener, apart from the receiver.
+    /// The tick index is taken from the control's [`TickInfo`], or counted if there is none.
+    /// Envelopes that don't target a tick are applied on the current tick.
+    pub fn tick(
+        &mut self,
+        dt: f32,
+        events: Vec<Envelope<E>>,
+        ctrl: TickLoopControl,
+        time: Instant,
+    ) -> Result<S, SaunterError> {
+        let (current, substep) = ctrl
+            .tick_info()
+            .map_or((self.tick, 0), |info| (info.tick, info.substep));
+        let mut rollback_to: Option<usize> = None;
+        for envelope in events {
+            let tick = envelope.tick().unwrap_or(current);
+            let event = envelope.event;
+            if tick >= current {
+                self.pending.entry(tick).or_default().push(event);
+                continue;
+            }
+
+            match self.history.front().map(|oldest| oldest.tick) {
+                Some(oldest) if tick >= oldest => {
+                    // The first sub-step of the tick.
+                    let index = self.history.partition_point(|saved| saved.tick < tick);
+                    self.history[index].events.push(event);
+                    rollback_to = Some(rollback_to.map_or(index, |other| other.min(index)));
+                }
+                _ => log::warn!(
+                    "dropping event for tick {}, it is too late to roll back to",
+                    tick
+                ),
+            }
+        }
+
+        if let Some(index) = rollback_to {
+            self.resimulate(index, &ctrl);
+        }
+
+        let events = match substep {
+            0 => self.pending.remove(&current).unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        self.simulate(current, substep, events, dt, ctrl, time)
+    }
+
+    /// Restores the state saved at `index` in the history and runs every tick since again.
+    fn resimulate(&mut self, index: usize, ctrl: &TickLoopControl) {
+        let replayed: Vec<_> = self.history.drain(index..).collect();
+        let Some(first) = replayed.first() else {
+            return;
+        };
+        log::debug!(
+            "rolling back {} ticks to tick {}",
+            replayed.len(),
+            first.tick
+        );
+        self.state = first.state.clone();
+
+        let mut corrected = None;
+        for saved in replayed {
+            let ctrl = ctrl.with_tick_info(saved.tick_info);
+            let result = self.simulate(
+                saved.tick,
+                saved.substep,
+                saved.events,
+                saved.dt,
+                ctrl,
+                saved.time,
+            );
+            if let Ok(snapshot) = result {
+                corrected = Some(snapshot);
+            }
+        }
+
+        // The tick loop moves this into `last_snapshot` when it publishes the current tick.
+        if let Some(snapshot) = corrected {
+            self.snapshots.write().unwrap().new_snapshot = Some(snapshot);
+        }
+    }
+
+    /// Saves the current state and runs the listener for `tick`, keeping the saved states of the last `max_rollback` ticks.
+    fn simulate(
+        &mut self,
+        tick: u64,
+        substep: u32,
+        events: Vec<E>,
+        dt: f32,
+        ctrl: TickLoopControl,
+        time: Instant,
+    ) -> Result<S, SaunterError> {
+        if self.max_rollback > 0 {
+            while self
+                .history
+                .front()
+                .is_some_and(|oldest| oldest.tick + self.max_rollback as u64 <= tick)
+            {
+                self.history.pop_front();
+            }
+            self.history.push_back(SavedTick {
+                tick,
+                substep,
+                state: self.state.clone(),
+                events: events.clone(),
+                dt,
+                time,
+                tick_info: ctrl.tick_info().copied(),
+            });
+        }
+
+        let result = (self.listener)(&mut self.state, dt, events, ctrl, time);
+        self.tick = tick + 1;
+        result
+    }
+}
@@ -0,0 +1,251 @@
+//! Contains the [`Scheduler`], which runs several tick loops on one thread, or a few.
+//! The scheduler ticks at a base rate, and every hosted loop runs on every n-th of those frames, so a game can run physics at 120 TPS,
+//! networking at 30 TPS and AI at 10 TPS without a thread and a sleep for each.
+//! Within a frame, loops run in the order they were added, and every loop publishes to its own [`Snapshots`].
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::error::SaunterError;
+use crate::event::EventSender;
+use crate::snapshot::{Snapshot, Snapshots};
+use crate::stats::TickSample;
+use crate::tickloop::{Step, TickLoop, TickLoopControl, TickLoopState};
+
+/// A tick loop of any snapshot and event type.
+trait HostedLoop: Send {
+    fn advance(&mut self, tick_time: Instant, due: Instant);
+    fn finish(&mut self);
+}
+
+impl<S: Snapshot + Send + Sync, E: Send> HostedLoop for TickLoop<S, E> {
+    fn advance(&mut self, tick_time: Instant, due: Instant) {
+        TickLoop::advance_at(self, tick_time, due);
+    }
+
+    fn finish(&mut self) {
+        TickLoop::finish(self);
+    }
+}
+
+type SharedLoop = Arc<Mutex<Box<dyn HostedLoop>>>;
+
+struct Hosted {
+    /// Shared with the worker threads of [`Scheduler::start_parallel`], which only lock it while the scheduler waits for them.
+    tick_loop: SharedLoop,
+    /// The loop runs on every frame whose index is a multiple of this.
+    divisor: u64,
+}
+
+/// A loop to advance to a frame time on a worker thread, with the time the frame was due.
+type Job = (SharedLoop, Instant, Instant);
+
+/// The channels to the worker threads of [`Scheduler::start_parallel`].
+struct Workers {
+    jobs: Sender<Job>,
+    /// Every job reports back once it is done, with the panic if the loop panicked.
+    done: Receiver<thread::Result<()>>,
+}
+
+/// Runs several tick loops at integer fractions of a base tick rate.
+/// All hosted loops share the scheduler's [`TickLoopControl`], so pausing, stopping or scaling time applies to all of them,
+/// and snapshots of every loop are stamped with times on the same clock.
+pub struct Scheduler {
+    base_length: Duration,
+    control: TickLoopControl,
+    loops: Vec<Hosted>,
+    frame: u64,
+    /// When the next frame is due, on the scheduler's clock.
+    next_frame_time: Option<Instant>,
+    /// When the previous frame ended, on the wall clock. Reset while paused.
+    last_frame_end: Option<Instant>,
+    /// Whether the scheduler was behind after the previous frame.
+    behind: bool,
+}
+
+impl Scheduler {
+    /// Creates a scheduler that runs frames at `base_tps`.
+    pub fn new(base_tps: f32) -> Self {
+        Scheduler {
+            base_length: Duration::from_secs_f32(1.0 / base_tps),
+            control: TickLoopControl::new(Default::default()),
+            loops: Vec::new(),
+            frame: 0,
+            next_frame_time: None,
+            last_frame_end: None,
+            behind: false,
+        }
+    }
+
+    /// Returns a [`TickLoopControl`] that controls every loop on the scheduler.
+    /// Its [`stats`](TickLoopControl::stats) report the scheduler's frames, while every loop keeps its own.
+    pub fn control(&self) -> TickLoopControl {
+        self.control.clone()
+    }
+
+    /// Adds a listener that runs on every `divisor`-th frame, at the base rate divided by `divisor`.
+    /// Returns an [`EventSender`] to send events to the listener, and the snapshots it publishes.
+    pub fn add<S, E, F>(
+        &mut self,
+        listener: F,
+        divisor: u32,
+    ) -> (EventSender<E>, Arc<RwLock<Snapshots<S>>>)
+    where
+        S: Snapshot + Send + Sync + 'static,
+        E: Send + 'static,
+        F: FnMut(f32, Vec<E>, TickLoopControl, Instant) -> Result<S, SaunterError> + Send + 'static,
+    {
+        let (tick_loop, event_sender, _, snapshots) = TickLoop::init(listener, 1.0);
+        self.add_loop(tick_loop, divisor);
+        (event_sender, snapshots)
+    }
+
+    /// Adds an existing tick loop that runs on every `divisor`-th frame, for example one with a recorder or reducer.
+    /// The loop's tick rate is replaced, and it is controlled by the scheduler's control from now on.
+    /// Its [message channels](TickLoop::with_messages), dropped event count and [stats](TickLoopControl::stats) stay its own,
+    /// so a control taken from the loop before adding it still reports them.
+    pub fn add_loop<S, E>(&mut self, mut tick_loop: TickLoop<S, E>, divisor: u32)
+    where
+        S: Snapshot + Send + Sync + 'static,
+        E: Send + 'static,
+    {
+        let divisor = divisor.max(1);
+        tick_loop.tick_length = self.base_length * divisor;
+        tick_loop.share_control(&self.control);
+        self.loops.push(Hosted {
+            tick_loop: Arc::new(Mutex::new(Box::new(tick_loop))),
+            divisor: divisor as u64,
+        });
+    }
+
+    /// Returns the index of the next frame.
+    pub fn current_frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Starts the scheduler, running every loop on the current thread. This blocks until the scheduler is stopped.
+    pub fn start(&mut self) {
+        self.start_parallel(1);
+    }
+
+    /// Starts the scheduler, running the loops that are due on the same frame on up to `threads` threads at once.
+    /// The worker threads are spawned once, and live until the scheduler is stopped.
+    /// Every loop of a frame finishes before the next frame starts, but loops on the same frame may run in any order.
+    /// This blocks until the scheduler is stopped.
+    pub fn start_parallel(&mut self, threads: usize) {
+        if threads <= 1 {
+            self.run(None);
+        } else {
+            thread::scope(|scope| {
+                let (job_sender, job_receiver) = mpsc::channel::<Job>();
+                let (done_sender, done_receiver) = mpsc::channel();
+                let job_receiver = Arc::new(Mutex::new(job_receiver));
+                for _ in 0..threads.min(self.loops.len()) {
+                    let jobs = job_receiver.clone();
+                    let done = done_sender.clone();
+                    scope.spawn(move || {
+                        // Workers stop once the job sender is dropped at the end of the run.
+                        while let Ok((tick_loop, frame_time, due)) = jobs.lock().unwrap().recv() {
+                            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                                tick_loop.lock().unwrap().advance(frame_time, due)
+                            }));
+                            if done.send(result).is_err() {
+                                break;
+                            }
+                        }
+                    });
+                }
+                self.run(Some(Workers {
+                    jobs: job_sender,
+                    done: done_receiver,
+                }));
+            });
+        }
+        self.loops
+            .iter()
+            .for_each(|hosted| hosted.tick_loop.lock().unwrap().finish());
+    }
+
+    fn run(&mut self, workers: Option<Workers>) {
+        loop {
+            match self.step(workers.as_ref()) {
+                Step::Wait(duration) => self.control.sleep(duration),
+                Step::Paused => self.control.wait_while_paused(),
+                Step::Stopped => break,
+            }
+        }
+    }
+
+    /// Runs the next frame if it is due, and returns what to do before the next step.
+    fn step(&mut self, workers: Option<&Workers>) -> Step {
+        match self.control.state() {
+            TickLoopState::Stopped => return Step::Stopped,
+            TickLoopState::Paused => {
+                self.last_frame_end = None;
+                return Step::Paused;
+            }
+            TickLoopState::Running => {}
+        }
+
+        let clock = self.control.clock().clone();
+        let frame_time = clock.now();
+        let due = *self.next_frame_time.get_or_insert(frame_time);
+        if frame_time < due {
+            return Step::Wait(clock.wall_duration(due - frame_time));
+        }
+
+        let started = Instant::now();
+        let frame = self.frame;
+        let due_loops: Vec<_> = self
+            .loops
+            .iter()
+            .filter(|hosted| frame.is_multiple_of(hosted.divisor))
+            .map(|hosted| &hosted.tick_loop)
+            .collect();
+        match workers {
+            Some(workers) if due_loops.len() > 1 => {
+                for tick_loop in &due_loops {
+                    // The workers only stop once the job sender is dropped, so sending cannot fail.
+                    let _ = workers.jobs.send((Arc::clone(tick_loop), frame_time, due));
+                }
+                for _ in 0..due_loops.len() {
+                    if let Ok(Err(payload)) = workers.done.recv() {
+                        panic::resume_unwind(payload);
+                    }
+                }
+            }
+            _ => due_loops
+                .iter()
+                .for_each(|tick_loop| tick_loop.lock().unwrap().advance(frame_time, due)),
+        }
+        self.frame += 1;
+
+        let ended = Instant::now();
+        let duration = ended - started;
+        self.control.record_tick(TickSample {
+            start: started,
+            duration,
+            listener: duration,
+            sleep: self
+                .last_frame_end
+                .map_or(Duration::ZERO, |end| started.saturating_duration_since(end)),
+            jitter: clock.wall_duration(frame_time - due),
+            overrun: duration > clock.wall_duration(self.base_length),
+        });
+        self.last_frame_end = Some(ended);
+
+        // Like the tick loop, frames that fall behind are caught up on without waiting.
+        let next_frame_time = due + self.base_length;
+        self.next_frame_time = Some(next_frame_time);
+        let now = clock.now();
+        let behind = now > next_frame_time;
+        if behind && !self.behind {
+            self.control.record_catch_up();
+        }
+        self.behind = behind;
+        Step::Wait(clock.wall_duration(next_frame_time.saturating_duration_since(now)))
+    }
+}
@@ -3,8 +3,8 @@
 //! The snapshot is then put into a Snapshots, which is used to interpolate between snapshots outside of the tick loop.
 //! In general, it is advised to put as little data as possible into your snapshot, as it is moved around in memory quite a bit.
 
-use crate::{error::SaunterError, interpolate::Interpolate};
-use std::{fmt::Debug, mem, time::Instant};
+use crate::{error::SaunterError, interpolate::Interpolate, time::SimClock};
+use std::{fmt::Debug, time::Instant};
 
 // A snapshot of the state of the game engine. It also must store the time of creation in some way.
 pub trait Snapshot: Interpolate + Debug {
@@ -13,6 +13,7 @@ pub trait Snapshot: Interpolate + Debug {
 }
 
 /// A struct that holds the last snapshot and the most recent snapshot. It is used to interpolate between the two snapshots.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Snapshots<T: Snapshot> {
     pub last_snapshot: Option<T>,
     pub new_snapshot: Option<T>,
@@ -33,16 +34,48 @@ impl<T: Snapshot> Snapshots<T> {
         t: f32,
         interpolation: impl Fn(f32) -> f32,
     ) -> Result<T, SaunterError> {
-        match (&self.last_snapshot.as_ref(), &self.new_snapshot.as_ref()) {
-            (Some(ref last), Some(ref new)) => {
+        match (self.last_snapshot.as_ref(), self.new_snapshot.as_ref()) {
+            (Some(last), Some(new)) => {
                 Ok(<T as Interpolate>::interpolate(last, new, t, interpolation))
             }
             _ => Err(SaunterError::TooFewSnapshots),
         }
     }
 
+    /// Returns how far to interpolate from the last to the new snapshot right now, for [`interpolate_ticks`](Snapshots::interpolate_ticks).
+    /// `clock` must be the clock of the loop that created the snapshots, as their times are on that clock rather than the wall clock.
+    /// Rendering runs a tick behind the loop: `0.0` is when the new snapshot was created, and `1.0` is a full tick later.
+    pub fn alpha(&self, clock: &SimClock) -> Result<f32, SaunterError> {
+        match (self.last_snapshot.as_ref(), self.new_snapshot.as_ref()) {
+            (Some(last), Some(new)) => {
+                let tick_length = new.get_time().saturating_duration_since(*last.get_time());
+                if tick_length.is_zero() {
+                    return Ok(1.0);
+                }
+                let elapsed = clock.elapsed(*new.get_time());
+                Ok((elapsed.as_secs_f32() / tick_length.as_secs_f32()).min(1.0))
+            }
+            _ => Err(SaunterError::TooFewSnapshots),
+        }
+    }
+
+    /// Interpolates between the last tick and the new tick at the current time of `clock`. See [`alpha`](Snapshots::alpha).
+    pub fn interpolate_now(
+        &self,
+        clock: &SimClock,
+        interpolation: impl Fn(f32) -> f32,
+    ) -> Result<T, SaunterError> {
+        self.interpolate_ticks(self.alpha(clock)?, interpolation)
+    }
+
     /// Drops last tick and replaces it with new tick, and then replaces new tick with the new new tick.
     pub fn update(&mut self, new_snapshot: T) {
-        self.last_snapshot = mem::replace(&mut self.new_snapshot, Some(new_snapshot));
+        self.last_snapshot = self.new_snapshot.replace(new_snapshot);
+    }
+}
+
+impl<T: Snapshot> Default for Snapshots<T> {
+    fn default() -> Self {
+        Self::new()
     }
 }
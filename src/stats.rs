@@ -0,0 +1,181 @@
+//! Contains [`TickStats`], timing metrics of a tick loop over a sliding window.
+//! Read them from any [`TickLoopControl`](crate::tickloop::TickLoopControl) with [`stats`](crate::tickloop::TickLoopControl::stats),
+//! for example to draw a performance overlay, or to fail a CI run when ticks get too slow.
+//! Every loop has its own window, also when it runs on a [`Scheduler`](crate::scheduler::Scheduler), whose control reports its frames instead.
+//! All durations are measured on the wall clock.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// How far back the stats look, unless changed with [`set_stats_window`](crate::tickloop::TickLoopControl::set_stats_window).
+pub const DEFAULT_WINDOW: Duration = Duration::from_secs(1);
+
+/// The timing of a single tick.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TickSample {
+    /// When the tick started.
+    pub(crate) start: Instant,
+    /// How long the whole tick took, including draining events and publishing the snapshot.
+    pub(crate) duration: Duration,
+    /// How long the listener ran, over all sub-steps.
+    pub(crate) listener: Duration,
+    /// How long the loop waited between the end of the previous tick and the start of this one.
+    pub(crate) sleep: Duration,
+    /// How late the tick started.
+    pub(crate) jitter: Duration,
+    /// Whether the tick took longer than its tick length.
+    pub(crate) overrun: bool,
+}
+
+/// Collects the samples of the ticks within the window.
+pub(crate) struct StatsWindow {
+    window: Duration,
+    samples: VecDeque<TickSample>,
+    /// When the loop fell behind, on the wall clock.
+    catch_ups: VecDeque<Instant>,
+}
+
+impl StatsWindow {
+    pub(crate) fn new() -> Self {
+        StatsWindow {
+            window: DEFAULT_WINDOW,
+            samples: VecDeque::new(),
+            catch_ups: VecDeque::new(),
+        }
+    }
+
+    pub(crate) fn set_window(&mut self, window: Duration) {
+        self.window = window;
+        self.evict(Instant::now());
+    }
+
+    pub(crate) fn record(&mut self, sample: TickSample) {
+        self.samples.push_back(sample);
+        self.evict(sample.start);
+    }
+
+    /// Counts the start of a run of ticks that ran back to back to catch up.
+    pub(crate) fn record_catch_up(&mut self) {
+        let now = Instant::now();
+        self.catch_ups.push_back(now);
+        self.evict(now);
+    }
+
+    fn evict(&mut self, now: Instant) {
+        let window = self.window;
+        let expired = |start: Instant| now.saturating_duration_since(start) > window;
+        while self
+            .samples
+            .front()
+            .is_some_and(|oldest| expired(oldest.start))
+        {
+            self.samples.pop_front();
+        }
+        while self
+            .catch_ups
+            .front()
+            .is_some_and(|&oldest| expired(oldest))
+        {
+            self.catch_ups.pop_front();
+        }
+    }
+
+    pub(crate) fn stats(&self) -> TickStats {
+        let mut durations: Vec<Duration> = self.samples.iter().map(|s| s.duration).collect();
+        durations.sort();
+        let ticks = self.samples.len();
+        let average = |field: fn(&TickSample) -> Duration| {
+            if ticks == 0 {
+                Duration::ZERO
+            } else {
+                self.samples.iter().map(field).sum::<Duration>() / ticks as u32
+            }
+        };
+        let tps = match (self.samples.front(), self.samples.back()) {
+            (Some(first), Some(last)) if ticks > 1 && last.start > first.start => {
+                (ticks - 1) as f32 / (last.start - first.start).as_secs_f32()
+            }
+            _ => 0.0,
+        };
+
+        TickStats {
+            ticks,
+            average: average(|s| s.duration),
+            min: durations.first().copied().unwrap_or_default(),
+            max: durations.last().copied().unwrap_or_default(),
+            listener_time: average(|s| s.listener),
+            sleep_time: average(|s| s.sleep),
+            jitter: average(|s| s.jitter),
+            max_jitter: self
+                .samples
+                .iter()
+                .map(|s| s.jitter)
+                .max()
+                .unwrap_or_default(),
+            overruns: self.samples.iter().filter(|s| s.overrun).count() as u64,
+            catch_up_bursts: self.catch_ups.len() as u64,
+            tps,
+            durations,
+        }
+    }
+}
+
+/// Tick timings over the loop's sliding window. Averages are per tick, and counts only cover the window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TickStats {
+    /// The number of ticks in the window.
+    pub ticks: usize,
+    pub average: Duration,
+    pub min: Duration,
+    pub max: Duration,
+    /// The average time spent in the listener, or in the due loops for the frames of a scheduler.
+    pub listener_time: Duration,
+    /// The average time spent waiting for the tick.
+    pub sleep_time: Duration,
+    /// The average time ticks started later than they were due.
+    pub jitter: Duration,
+    pub max_jitter: Duration,
+    /// How many ticks in the window took longer than the tick length.
+    pub overruns: u64,
+    /// How many times within the window the loop fell behind and ran ticks back to back to catch up.
+    pub catch_up_bursts: u64,
+    /// The number of ticks per second the loop actually ran at.
+    pub tps: f32,
+    /// Sorted tick durations, for percentiles.
+    durations: Vec<Duration>,
+}
+
+impl TickStats {
+    /// Returns the tick duration that `percentile` percent of the ticks in the window took at most, for example `99.0`.
+    pub fn percentile(&self, percentile: f32) -> Duration {
+        if self.durations.is_empty() {
+            return Duration::ZERO;
+        }
+        let rank =
+            (percentile.clamp(0.0, 100.0) / 100.0 * (self.durations.len() - 1) as f32).round();
+        self.durations[rank as usize]
+    }
+
+    /// Returns the durations of the ticks in the window, from the shortest to the longest.
+    pub fn durations(&self) -> &[Duration] {
+        &self.durations
+    }
+
+    /// Counts the ticks in the window by duration, in buckets of `bucket_width`.
+    /// The bucket at index `i` counts the ticks that took from `i * bucket_width` up to `(i + 1) * bucket_width`,
+    /// and the last bucket holds the longest tick. Empty if there are no ticks or `bucket_width` is zero.
+    pub fn histogram(&self, bucket_width: Duration) -> Vec<usize> {
+        let Some(max) = self.durations.last() else {
+            return Vec::new();
+        };
+        if bucket_width.is_zero() {
+            return Vec::new();
+        }
+        let bucket = |duration: &Duration| (duration.as_nanos() / bucket_width.as_nanos()) as usize;
+        let mut buckets = vec![0; bucket(max) + 1];
+        for duration in &self.durations {
+            buckets[bucket(duration)] += 1;
+        }
+        buckets
+    }
+}
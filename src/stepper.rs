@@ -0,0 +1,145 @@
+//! Contains the [`FixedStepper`], which runs a tick loop from inside your own frame loop instead of on a thread of its own.
+//! Every frame, pass the frame time to the stepper. It runs the ticks that fit into the accumulated time, and returns how far the
+//! simulation is into the next tick, to interpolate between the last two snapshots with [`Snapshots::interpolate_ticks`].
+//! The stepper takes a regular [`TickLoop`], with the same listener and [`Snapshots`], so a game can pick the threaded or the single-threaded mode at startup:
+//!
+//! ```ignore
+//! let (tick_loop, event_sender, ctrl, snapshots) = TickLoop::init(listener, TPS);
+//! if config.single_threaded {
+//!     let mut stepper = FixedStepper::new(tick_loop);
+//!     // Every frame:
+//!     let alpha = stepper.update(frame_time);
+//! } else {
+//!     thread::spawn(move || tick_loop.start());
+//! }
+//! ```
+
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::snapshot::{Snapshot, Snapshots};
+use crate::tickloop::{TickLoop, TickLoopControl, TickLoopState};
+
+/// Runs a [`TickLoop`] on the caller's thread, using the "fix your timestep" accumulator.
+/// Frame time is scaled by the loop's time scale, and does not accumulate while the loop is paused or stopped.
+pub struct FixedStepper<S: Snapshot, E: Send> {
+    tick_loop: TickLoop<S, E>,
+    /// Frame time that has not been simulated yet.
+    accumulator: Duration,
+    max_ticks_per_frame: Option<u32>,
+    /// The time of the next tick, on the loop's clock.
+    next_tick_time: Option<Instant>,
+}
+
+impl<S: Snapshot + Send + Sync, E: Send> FixedStepper<S, E> {
+    pub fn new(tick_loop: TickLoop<S, E>) -> Self {
+        FixedStepper {
+            tick_loop,
+            accumulator: Duration::ZERO,
+            max_ticks_per_frame: None,
+            next_tick_time: None,
+        }
+    }
+
+    /// Limits how many ticks a single frame can run. Time beyond that is dropped, so a slow frame does not make the next ones slower.
+    pub fn with_max_ticks_per_frame(mut self, max_ticks: u32) -> Self {
+        self.max_ticks_per_frame = Some(max_ticks.max(1));
+        self
+    }
+
+    pub fn control(&self) -> TickLoopControl {
+        self.tick_loop.control()
+    }
+
+    pub fn snapshots(&self) -> Arc<RwLock<Snapshots<S>>> {
+        self.tick_loop.snapshots()
+    }
+
+    pub fn tick_loop(&self) -> &TickLoop<S, E> {
+        &self.tick_loop
+    }
+
+    pub fn into_inner(self) -> TickLoop<S, E> {
+        self.tick_loop
+    }
+
+    /// Adds the time of a frame, and returns how many ticks are due.
+    /// Run them with [`tick`](FixedStepper::tick), or use [`update`](FixedStepper::update) to do both.
+    pub fn accumulate(&mut self, frame_time: Duration) -> u32 {
+        let control = self.tick_loop.control();
+        match control.state() {
+            TickLoopState::Running => {}
+            TickLoopState::Paused => return 0,
+            TickLoopState::Stopped => {
+                self.tick_loop.finish();
+                return 0;
+            }
+        }
+        self.accumulator += frame_time.mul_f64(control.clock().time_scale());
+
+        let tick_length = self.tick_loop.tick_length;
+        let due = (self.accumulator.as_secs_f64() / tick_length.as_secs_f64()) as u32;
+        match self.max_ticks_per_frame {
+            Some(max_ticks) if due > max_ticks => {
+                log::debug!(
+                    "dropping {} ticks that did not fit into the frame",
+                    due - max_ticks
+                );
+                self.accumulator -= tick_length * (due - max_ticks);
+                max_ticks
+            }
+            _ => due,
+        }
+    }
+
+    /// Runs a single tick, and takes its length off the accumulated time.
+    pub fn tick(&mut self) {
+        let tick_length = self.tick_loop.tick_length;
+        let tick_time = *self
+            .next_tick_time
+            .get_or_insert_with(|| self.tick_loop.control().clock().now());
+        // The tick became due when the accumulated time first covered it.
+        let late = self.accumulator.saturating_sub(tick_length);
+        self.tick_loop.advance(tick_time, late);
+        self.next_tick_time = Some(tick_time + tick_length);
+        self.accumulator = self.accumulator.saturating_sub(tick_length);
+        self.tick_loop.set_behind(self.accumulator >= tick_length);
+    }
+
+    /// Adds the time of a frame, runs every tick that is due, and returns the interpolation [`alpha`](FixedStepper::alpha).
+    /// If a tick pauses or stops the loop, the rest of the frame's ticks are dropped along with the accumulated time.
+    pub fn update(&mut self, frame_time: Duration) -> f32 {
+        for _ in 0..self.accumulate(frame_time) {
+            if !self.still_running() {
+                break;
+            }
+            self.tick();
+        }
+        self.alpha()
+    }
+
+    /// Returns whether the loop is running, dropping the accumulated time if it is not, and finishing the loop if it was stopped.
+    fn still_running(&mut self) -> bool {
+        let state = self.tick_loop.control().state();
+        if state != TickLoopState::Running {
+            self.accumulator = Duration::ZERO;
+            self.tick_loop.set_behind(false);
+        }
+        if state == TickLoopState::Stopped {
+            self.tick_loop.finish();
+        }
+        state == TickLoopState::Running
+    }
+
+    /// Returns how far the simulation is into the next tick, from `0.0` to `1.0`.
+    /// Pass it to [`Snapshots::interpolate_ticks`] to render between the last two snapshots.
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator.as_secs_f32() / self.tick_loop.tick_length.as_secs_f32()).min(1.0)
+    }
+}
+
+impl<S: Snapshot + Send + Sync, E: Send> From<TickLoop<S, E>> for FixedStepper<S, E> {
+    fn from(tick_loop: TickLoop<S, E>) -> Self {
+        Self::new(tick_loop)
+    }
+}
@@ -4,39 +4,296 @@
 //! the tick loop will run as fast as possible until it catches back up.
 
 use log;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender};
-use std::sync::{Arc, Mutex, RwLock};
+use std::sync::{Arc, Condvar, Mutex, RwLock};
 use std::time::{Duration, Instant};
 
 use crate::error::SaunterError;
+use crate::event::{self, EventReducer, EventSender, EventSource, OverflowPolicy};
+use crate::interpolate::{linear, Interpolate};
+use crate::replay::{RecordSink, RecordedTick};
 use crate::snapshot::{Snapshot, Snapshots};
+use crate::stats::{StatsWindow, TickSample, TickStats};
+use crate::time::SimClock;
 type Listener<T, E> =
     dyn FnMut(f32, Vec<E>, TickLoopControl, Instant) -> Result<T, SaunterError> + Send;
+type StateCallback = dyn FnMut(TickLoopState) + Send;
+type SubstepMerge<S> = dyn FnMut(S, S) -> S + Send;
+/// The senders of the loop's message channels, by message type.
+type Outbox = HashMap<TypeId, Box<dyn Any + Send>>;
 
+/// Enters a `tracing` span until the end of the current scope, when the `tracing` feature is enabled.
+macro_rules! span {
+    ($name:literal, $($fields:tt)*) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!($name, $($fields)*).entered();
+    };
+}
+
+/// How long before the end of a sleep between ticks the loop stops parking and spins instead.
+const SPIN_MARGIN: Duration = Duration::from_millis(1);
+
+/// What the loop should do after a call to [`TickLoop::step`].
+pub(crate) enum Step {
+    /// Wait this long before the next tick.
+    Wait(Duration),
+    Paused,
+    Stopped,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TickLoopState {
     Running,
     Stopped,
     Paused,
 }
 
+/// Which `dt` the tick loop passes to the listener.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TimestepMode {
+    /// Every tick passes the tick length, even when it started late. Late ticks are caught up on.
+    #[default]
+    Fixed,
+    /// Every tick passes the time since the previous tick started. Late ticks are not caught up on.
+    Variable,
+    /// Like [`Variable`](TimestepMode::Variable), but the time is clamped between `min` and `max`.
+    SemiFixed { min: Duration, max: Duration },
+}
+
+#[derive(Clone)]
 pub struct TickLoopControl {
     state: Arc<Mutex<TickLoopState>>,
+    /// Notified whenever `state` changes.
+    state_changed: Arc<Condvar>,
+    callbacks: Arc<Mutex<Vec<Box<StateCallback>>>>,
+    clock: SimClock,
+    dropped_events: Arc<AtomicU64>,
+    /// The senders of the channels created by [`messages`](TickLoopControl::messages). Every loop has its own.
+    outbox: Arc<Mutex<Outbox>>,
+    stats: Arc<Mutex<StatsWindow>>,
+    /// Only set on the control passed to the listener.
+    tick_info: Option<TickInfo>,
+}
+
+/// Information about a tick, available to the listener through [`TickLoopControl::tick_info`].
+/// Store it in your snapshots to keep both the simulated and the wall clock time of every tick.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TickInfo {
+    pub tick: u64,
+    /// The index of the sub-step within the tick, `0` unless the loop [sub-steps](TickLoop::with_substeps).
+    pub substep: u32,
+    /// The time of the tick or sub-step on the loop's [`SimClock`]. This is the time passed to the listener.
+    #[cfg_attr(feature = "serde", serde(with = "crate::time::serde_instant"))]
+    pub time: Instant,
+    /// The tick length of the loop.
+    pub nominal_dt: Duration,
+    /// The time since the previous tick started, on the loop's clock. The listener's `dt` depends on the loop's [`TimestepMode`].
+    pub measured_dt: Duration,
+    /// The time on the wall clock when the tick ran.
+    #[cfg_attr(feature = "serde", serde(with = "crate::time::serde_instant"))]
+    pub wall_time: Instant,
+    /// How many times faster than the wall clock the loop's clock was running.
+    pub time_scale: f32,
+}
+
+impl Interpolate for TickInfo {
+    fn interpolate(start: &Self, end: &Self, t: f32, interpolation: impl Fn(f32) -> f32) -> Self {
+        let t = interpolation(t);
+        TickInfo {
+            // Lerp in `f64`, as `f32` can't represent tick indices above 2^24.
+            tick: (start.tick as f64 + (end.tick as f64 - start.tick as f64) * t as f64) as u64,
+            substep: u32::interpolate(&start.substep, &end.substep, t, linear),
+            time: Instant::interpolate(&start.time, &end.time, t, linear),
+            nominal_dt: Duration::interpolate(&start.nominal_dt, &end.nominal_dt, t, linear),
+            measured_dt: Duration::interpolate(&start.measured_dt, &end.measured_dt, t, linear),
+            wall_time: Instant::interpolate(&start.wall_time, &end.wall_time, t, linear),
+            time_scale: f32::interpolate(&start.time_scale, &end.time_scale, t, linear),
+        }
+    }
 }
 
 impl TickLoopControl {
+    pub(crate) fn new(dropped_events: Arc<AtomicU64>) -> Self {
+        TickLoopControl {
+            state: Arc::new(Mutex::new(TickLoopState::Running)),
+            state_changed: Arc::new(Condvar::new()),
+            callbacks: Arc::new(Mutex::new(Vec::new())),
+            clock: SimClock::new(),
+            tick_info: None,
+            dropped_events,
+            outbox: Default::default(),
+            stats: Arc::new(Mutex::new(StatsWindow::new())),
+        }
+    }
+
+    /// Creates a channel for messages of type `M` sent from the listener with [`send_message`](TickLoopControl::send_message),
+    /// for example commands for the windowing thread. Every message type has its own channel, and fails if `M` already has one.
+    /// Prefer [`TickLoop::with_messages`] to create the channels along with the loop.
+    pub fn messages<M: Send + 'static>(&self) -> Result<Receiver<M>, SaunterError> {
+        let mut outbox = self.outbox.lock().unwrap();
+        if outbox.contains_key(&TypeId::of::<M>()) {
+            return Err(SaunterError::MessageChannelExists);
+        }
+        let (sender, reciever) = mpsc::channel::<M>();
+        outbox.insert(TypeId::of::<M>(), Box::new(sender));
+        Ok(reciever)
+    }
+
+    /// Sends a message to the receiver created by [`messages`](TickLoopControl::messages).
+    /// Fails if there is no receiver for messages of type `M`, or it was dropped.
+    pub fn send_message<M: Send + 'static>(&self, message: M) -> Result<(), SaunterError> {
+        let outbox = self.outbox.lock().unwrap();
+        let sender = outbox
+            .get(&TypeId::of::<M>())
+            .and_then(|sender| sender.downcast_ref::<Sender<M>>())
+            .ok_or(SaunterError::MessageNotDelivered)?;
+        sender
+            .send(message)
+            .map_err(|_| SaunterError::MessageNotDelivered)
+    }
+
+    /// Returns the number of events the event channel has dropped because it was full.
+    pub fn dropped_events(&self) -> u64 {
+        self.dropped_events.load(Ordering::Relaxed)
+    }
+
+    /// Returns the timing metrics of the loop's recent ticks.
+    /// The control of a [`Scheduler`](crate::scheduler::Scheduler) reports its frames, while hosted loops keep their own.
+    pub fn stats(&self) -> TickStats {
+        self.stats.lock().unwrap().stats()
+    }
+
+    pub(crate) fn record_tick(&self, sample: TickSample) {
+        self.stats.lock().unwrap().record(sample);
+    }
+
+    pub(crate) fn record_catch_up(&self) {
+        self.stats.lock().unwrap().record_catch_up();
+    }
+
+    /// Sets how far back [`stats`](TickLoopControl::stats) look. Defaults to one second.
+    pub fn set_stats_window(&self, window: Duration) {
+        self.stats.lock().unwrap().set_window(window);
+    }
+
+    /// Returns the clock the loop stamps its ticks with. It stops while the loop is paused,
+    /// so use [`SimClock::elapsed`] on a snapshot's time to interpolate without jumping after a pause.
+    pub fn clock(&self) -> &SimClock {
+        &self.clock
+    }
+
+    /// Makes the loop's clock run `scale` times faster than the wall clock. Ticks still advance the simulation by the same `dt`,
+    /// so a scale of `2.0` runs twice as many ticks per second, and `0.5` half as many.
+    /// Scales of zero or below are ignored, use [`pause`](TickLoopControl::pause) instead.
+    pub fn set_time_scale(&self, scale: f32) {
+        let _state = self.state.lock().unwrap();
+        self.clock.set_time_scale(scale as f64);
+        self.state_changed.notify_all();
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        self.clock.time_scale() as f32
+    }
+
+    /// Returns information about the tick being run, when called on the control passed to the listener.
+    pub fn tick_info(&self) -> Option<&TickInfo> {
+        self.tick_info.as_ref()
+    }
+
+    /// Returns a clone of the control that reports `tick_info` to the listener.
+    pub(crate) fn with_tick_info(&self, tick_info: Option<TickInfo>) -> Self {
+        TickLoopControl {
+            tick_info,
+            ..self.clone()
+        }
+    }
+
+    /// Returns the current state of the loop.
+    pub fn state(&self) -> TickLoopState {
+        *self.state.lock().unwrap()
+    }
+
+    /// Blocks until the loop is in `state`, or `timeout` has passed. Returns whether the loop reached the state.
+    pub fn wait_for_state(&self, state: TickLoopState, timeout: Duration) -> bool {
+        let current = self.state.lock().unwrap();
+        let (current, _) = self
+            .state_changed
+            .wait_timeout_while(current, timeout, |current| *current != state)
+            .unwrap();
+        *current == state
+    }
+
+    /// Calls `callback` with the new state every time the state changes, on the thread that changed it.
+    /// The callback must not change the state itself.
+    pub fn on_state_change(&self, callback: impl FnMut(TickLoopState) + Send + 'static) {
+        self.callbacks.lock().unwrap().push(Box::new(callback));
+    }
+
     pub fn stop(&mut self) {
-        let mut state = self.state.lock().unwrap();
-        *state = TickLoopState::Stopped;
+        self.set_state(TickLoopState::Stopped);
     }
 
     pub fn pause(&mut self) {
-        let mut state = self.state.lock().unwrap();
-        *state = TickLoopState::Paused;
+        self.set_state(TickLoopState::Paused);
     }
 
     pub fn resume(&mut self) {
+        self.set_state(TickLoopState::Running);
+    }
+
+    /// Sleeps for `duration`, returning early if the state or the time scale changes.
+    /// The end of the sleep is spun for accuracy, so a change may take up to [`SPIN_MARGIN`] to be noticed.
+    pub(crate) fn sleep(&self, duration: Duration) {
+        let deadline = Instant::now() + duration;
+        let parked = duration.saturating_sub(SPIN_MARGIN);
+        if !parked.is_zero() {
+            let state = self.state.lock().unwrap();
+            let changes = self.clock.changes();
+            let (_state, result) = self
+                .state_changed
+                .wait_timeout_while(state, parked, |state| {
+                    *state == TickLoopState::Running && self.clock.changes() == changes
+                })
+                .unwrap();
+            if !result.timed_out() {
+                return;
+            }
+        }
+        spin_sleep::sleep(deadline.saturating_duration_since(Instant::now()));
+    }
+
+    /// Parks the thread until the loop is no longer paused.
+    pub(crate) fn wait_while_paused(&self) {
+        let state = self.state.lock().unwrap();
+        let _state = self
+            .state_changed
+            .wait_while(state, |state| *state == TickLoopState::Paused)
+            .unwrap();
+    }
+
+    fn set_state(&self, new_state: TickLoopState) {
         let mut state = self.state.lock().unwrap();
-        *state = TickLoopState::Running;
+        if *state == new_state {
+            return;
+        }
+        match new_state {
+            TickLoopState::Paused => self.clock.pause(),
+            TickLoopState::Running => self.clock.resume(),
+            TickLoopState::Stopped => {}
+        }
+        *state = new_state;
+        drop(state);
+
+        self.state_changed.notify_all();
+        for callback in self.callbacks.lock().unwrap().iter_mut() {
+            callback(new_state);
+        }
     }
 }
 
@@ -45,115 +302,398 @@ impl TickLoopControl {
 pub struct TickLoop<S: Snapshot, E: Send> {
     pub listener: Box<Listener<S, E>>,
     pub tick_length: Duration,
-    reciever: Receiver<E>,
-    state: Arc<Mutex<TickLoopState>>,
+    reciever: Box<dyn EventSource<E>>,
+    control: TickLoopControl,
     snapshots: Arc<RwLock<Snapshots<S>>>,
+    tick: u64,
+    /// When tick `0` started, or would have started if the loop began at a later tick.
+    start_time: Option<Instant>,
+    recorder: Option<Box<dyn RecordSink<E>>>,
+    reducer: Option<Box<dyn EventReducer<E>>>,
+    timestep: TimestepMode,
+    /// When the previous tick started, on the loop's [`SimClock`].
+    last_tick_time: Option<Instant>,
+    substeps: u32,
+    merge_substeps: Option<Box<SubstepMerge<S>>>,
+    /// When the next tick is due, on the loop's [`SimClock`].
+    next_tick_time: Option<Instant>,
+    /// When the previous tick ended, on the wall clock. Reset while paused.
+    last_tick_end: Option<Instant>,
+    /// Whether the loop was behind after the previous tick.
+    behind: bool,
+    #[cfg(feature = "async")]
+    watch: Arc<crate::asynchronous::SnapshotWatch>,
 }
 
-impl<'a, S: Snapshot, E: Send> TickLoop<S, E> {
-    /// Creates a new Loop struct.
+impl<S: Snapshot, E: Send> TickLoop<S, E> {
+    /// Creates a new Loop struct, in the running state. Control it with the [`TickLoopControl`] from [`control`](TickLoop::control).
     /// It is recommended to use [`init`](TickLoop::init) instead.
     pub fn new<F>(
         listener: F,
         tps: f32,
-        reciever: Receiver<E>,
-        state: Arc<Mutex<TickLoopState>>,
+        reciever: impl EventSource<E> + 'static,
         snapshots: Arc<RwLock<Snapshots<S>>>,
     ) -> Self
     where
         F: FnMut(f32, Vec<E>, TickLoopControl, Instant) -> Result<S, SaunterError> + Send + 'static,
     {
         let tick_length = Duration::from_secs_f32(1.0 / tps);
+        let control = TickLoopControl::new(reciever.dropped_counter());
         TickLoop {
             listener: Box::new(listener),
             tick_length,
-            reciever,
+            reciever: Box::new(reciever),
             snapshots,
-            state,
+            control,
+            tick: 0,
+            start_time: None,
+            recorder: None,
+            reducer: None,
+            timestep: TimestepMode::Fixed,
+            last_tick_time: None,
+            substeps: 1,
+            merge_substeps: None,
+            next_tick_time: None,
+            last_tick_end: None,
+            behind: false,
+            #[cfg(feature = "async")]
+            watch: Arc::new(crate::asynchronous::SnapshotWatch::new()),
         }
     }
 
-    /// Creates a new Loop struct and returns a [`Sender`] to send events to the loop.
+    /// Records the events of every tick into `sink`, so the session can be [replayed](TickLoop::replay) later.
+    pub fn with_recorder(mut self, sink: impl RecordSink<E> + 'static) -> Self {
+        self.recorder = Some(Box::new(sink));
+        self
+    }
+
+    /// Passes the events of every tick through `reducer` before the listener runs.
+    /// Recordings hold the reduced events, so the reducer is not run again when they are replayed.
+    pub fn with_reducer(mut self, reducer: impl EventReducer<E> + 'static) -> Self {
+        self.reducer = Some(Box::new(reducer));
+        self
+    }
+
+    /// Sets which `dt` is passed to the listener. Defaults to [`TimestepMode::Fixed`].
+    pub fn with_timestep(mut self, timestep: TimestepMode) -> Self {
+        self.timestep = timestep;
+        self
+    }
+
+    /// Calls the listener `substeps` times per tick, each time with a `dt` of the tick length divided by `substeps`.
+    /// The tick's events are passed to the first sub-step, and only the snapshot of the last sub-step is published,
+    /// unless the snapshots are [merged](TickLoop::with_substep_merge).
+    pub fn with_substeps(mut self, substeps: u32) -> Self {
+        self.substeps = substeps.max(1);
+        self
+    }
+
+    /// Publishes the snapshots of all sub-steps of a tick folded together with `merge`, instead of the last one.
+    pub fn with_substep_merge(mut self, merge: impl FnMut(S, S) -> S + Send + 'static) -> Self {
+        self.merge_substeps = Some(Box::new(merge));
+        self
+    }
+
+    /// Bounds the event channel to `capacity` events. Events sent while it is full are handled according to `policy`.
+    /// A capacity of `0` is treated as `1`.
+    pub fn with_event_capacity(self, capacity: usize, policy: OverflowPolicy<E>) -> Self {
+        self.reciever.set_capacity(Some(capacity), policy);
+        self
+    }
+
+    /// Creates a channel for messages of type `M` that the listener sends with [`TickLoopControl::send_message`].
+    /// Panics if the loop already has a channel for `M`.
+    pub fn with_messages<M: Send + 'static>(self) -> (Self, Receiver<M>) {
+        let reciever = self
+            .control
+            .messages()
+            .expect("the tick loop already has a channel for this message type");
+        (self, reciever)
+    }
+
+    /// Returns a [`TickLoopControl`] to control the loop from another thread.
+    pub fn control(&self) -> TickLoopControl {
+        self.control.clone()
+    }
+
+    /// Returns the snapshots the loop publishes to.
+    pub fn snapshots(&self) -> Arc<RwLock<Snapshots<S>>> {
+        self.snapshots.clone()
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) fn watch(&self) -> Arc<crate::asynchronous::SnapshotWatch> {
+        self.watch.clone()
+    }
+
+    /// Returns the index of the next tick the loop will run.
+    pub fn current_tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Returns the index of the first tick that starts at or after `time`.
+    /// Before the loop has started, this is always the next tick.
+    pub fn tick_at(&self, time: Instant) -> u64 {
+        let time = self.control.clock.time_at(time);
+        tick_at(self.start_time, self.tick_length, self.tick, time)
+    }
+
+    /// Creates a new Loop struct and returns an [`EventSender`] to send events to the loop.
     pub fn init<F>(
         listener: F,
         tps: f32,
     ) -> (
         Self,
-        Sender<E>,
+        EventSender<E>,
         TickLoopControl,
         Arc<RwLock<Snapshots<S>>>,
     )
     where
+        E: 'static,
         F: FnMut(f32, Vec<E>, TickLoopControl, Instant) -> Result<S, SaunterError> + Send + 'static,
     {
-        let (event_sender, event_reciever) = mpsc::channel::<E>();
+        let (event_sender, event_reciever) = event::channel::<E>();
         let snapshots = Arc::new(RwLock::new(Snapshots::new()));
-        let state = Arc::new(Mutex::new(TickLoopState::Running));
-
-        (
-            Self::new(listener, tps, event_reciever, state.clone(), snapshots.clone()),
-            event_sender,
-            TickLoopControl {
-                state: state.clone(),
-            },
-            snapshots,
-        )
+        let tick_loop = Self::new(listener, tps, event_reciever, snapshots.clone());
+        let control = tick_loop.control();
+
+        (tick_loop, event_sender, control, snapshots)
     }
 
     /// Starts the loop. This function will block the current thread. So the loop should be sent to a new thread, and start called on it there.
+    /// While paused, the thread is parked until the loop is resumed or stopped. Sleeps between ticks end early when the state changes.
     pub fn start(&mut self) {
-        let mut deficit = Duration::from_secs_f32(0.0);
+        loop {
+            match self.step() {
+                Step::Wait(duration) => self.control.sleep(duration),
+                Step::Paused => self.control.wait_while_paused(),
+                Step::Stopped => break,
+            }
+        }
+        self.finish();
+    }
+
+    /// Runs a tick if the loop is running and the tick is due, and returns what to do before the next step.
+    /// Ticks are scheduled on the loop's [`SimClock`], so time spent paused is skipped rather than caught up on.
+    pub(crate) fn step(&mut self) -> Step {
+        match *self.control.state.lock().unwrap() {
+            TickLoopState::Stopped => return Step::Stopped,
+            TickLoopState::Paused => {
+                self.last_tick_end = None;
+                return Step::Paused;
+            }
+            TickLoopState::Running => {}
+        }
 
-        'a: loop {
-            let tick_time = std::time::Instant::now();
+        let clock = &self.control.clock;
+        let tick_time = clock.now();
+        let due = *self.next_tick_time.get_or_insert(tick_time);
+        if tick_time < due {
+            // Woken early, for example by a resume in the middle of the wait.
+            return Step::Wait(clock.wall_duration(due - tick_time));
+        }
 
-            match *self.state.lock().unwrap() {
-                TickLoopState::Stopped => break 'a,
-                TickLoopState::Paused => {
-                    spin_sleep::sleep(self.tick_length);
-                    continue 'a;
-                }
-                TickLoopState::Running => {}
+        self.advance(tick_time, tick_time - due);
+
+        // Automatically catch the loop back up when it falls behind, by running the next ticks without waiting.
+        // With a variable timestep, the next tick's `dt` covers the lost time instead.
+        let next_tick_time = match self.timestep {
+            TimestepMode::Fixed => due + self.tick_length,
+            _ => tick_time + self.tick_length,
+        };
+        self.next_tick_time = Some(next_tick_time);
+        let now = self.control.clock.now();
+        if now > next_tick_time && !self.behind {
+            log::debug!("tick loop fell behind by {:?}", now - next_tick_time);
+        }
+        self.set_behind(now > next_tick_time);
+        Step::Wait(
+            self.control
+                .clock
+                .wall_duration(next_tick_time.saturating_duration_since(now)),
+        )
+    }
+
+    /// Drains the events for the next tick and runs it at `tick_time`, without checking whether it is due.
+    /// `late` is how long after it was due the tick runs, on the loop's clock.
+    pub(crate) fn advance(&mut self, tick_time: Instant, late: Duration) {
+        span!("tick", tick = self.tick);
+        let started = Instant::now();
+        let sleep = self
+            .last_tick_end
+            .map_or(Duration::ZERO, |end| started.saturating_duration_since(end));
+        let jitter = self.control.clock.wall_duration(late);
+
+        let start_time = *self.start_time.get_or_insert_with(|| {
+            tick_time
+                .checked_sub(self.tick_length.mul_f64(self.tick as f64))
+                .unwrap_or(tick_time)
+        });
+        let (tick_length, tick) = (self.tick_length, self.tick);
+        let events = {
+            span!("drain_events", tick);
+            let clock = &self.control.clock;
+            let events = self.reciever.events_for(tick, &|time| {
+                tick_at(Some(start_time), tick_length, tick, clock.time_at(time))
+            });
+            match self.reducer.as_mut() {
+                Some(reducer) => reducer.reduce(events),
+                None => events,
             }
+        };
+        let listener = self.run_tick(events, tick_time);
+
+        let ended = Instant::now();
+        let duration = ended - started;
+        self.control.record_tick(TickSample {
+            start: started,
+            duration,
+            listener,
+            sleep,
+            jitter,
+            overrun: duration > self.control.clock.wall_duration(self.tick_length),
+        });
+        self.last_tick_end = Some(ended);
+    }
+
+    /// Runs the next tick at `tick_time` for a host that decides when it is due, such as the [`Scheduler`](crate::scheduler::Scheduler).
+    /// Jitter and catch-up bursts are measured against `due`, the time the tick was due on the loop's clock.
+    pub(crate) fn advance_at(&mut self, tick_time: Instant, due: Instant) {
+        self.advance(tick_time, tick_time.saturating_duration_since(due));
+        self.set_behind(self.control.clock.now() > due + self.tick_length);
+    }
+
+    /// Sets whether the next tick is already due after the current one, and counts a catch-up burst when the loop falls behind.
+    pub(crate) fn set_behind(&mut self, behind: bool) {
+        if behind && !self.behind {
+            self.control.record_catch_up();
+        }
+        self.behind = behind;
+    }
+
+    /// Replaces the loop's control with `control`, keeping the loop's own dropped event counter, message channels and stats.
+    pub(crate) fn share_control(&mut self, control: &TickLoopControl) {
+        self.control = TickLoopControl {
+            dropped_events: self.control.dropped_events.clone(),
+            outbox: self.control.outbox.clone(),
+            stats: self.control.stats.clone(),
+            ..control.clone()
+        };
+    }
+
+    /// Called once the loop has stopped.
+    pub(crate) fn finish(&mut self) {
+        #[cfg(feature = "async")]
+        self.watch.close();
+    }
 
-            let events = self.reciever.try_iter().collect();
-
-            if let Ok(snapshot) = (self.listener)(
-                self.tick_length.as_secs_f32(),
-                events,
-                TickLoopControl {
-                    state: self.state.clone(),
-                },
-                tick_time,
-            ) {
-                let mut tick_wlock = self.snapshots.write().unwrap();
-                log::debug!("lock aquired {:?}", std::time::Instant::now());
-                (*tick_wlock).update(snapshot);
-                // Drop the write lock so the read lock can be acquired.
+    /// Feeds a recorded session back into the listener, one tick at a time.
+    /// Ticks run back to back without waiting for the wall clock, and the time passed to the listener advances by exactly one tick length per tick.
+    /// Ticks missing from the recording run with no events, and recorded ticks the loop has already run are skipped.
+    /// Replaying stops early if the loop is stopped.
+    pub fn replay(&mut self, recording: impl IntoIterator<Item = RecordedTick<E>>) {
+        let start = Instant::now();
+
+        for RecordedTick { tick, events } in recording {
+            if tick < self.tick {
+                log::warn!(
+                    "skipping recorded tick {}, the loop is already past it",
+                    tick
+                );
+                continue;
             }
-            log::debug!("lock dropped {:?}", std::time::Instant::now());
-
-            let elapsed = tick_time.elapsed();
-            if elapsed < self.tick_length {
-                let mut sleep_dur = self.tick_length - elapsed;
-                // Automatically catch the loop back up when there is a deficit.
-                log::debug!("deficit: {:?}", deficit);
-                if deficit < sleep_dur {
-                    sleep_dur -= deficit;
-                    deficit = Duration::from_secs_f32(0.0);
-                } else {
-                    deficit -= sleep_dur;
-                    sleep_dur *= 0;
+
+            let mut events = Some(events);
+            while self.tick <= tick {
+                if let TickLoopState::Stopped = *self.control.state.lock().unwrap() {
+                    return;
                 }
 
-                spin_sleep::sleep(self.tick_length - elapsed);
-            } else {
-                let current_tick_deficit = elapsed - self.tick_length;
-                deficit += current_tick_deficit;
-                log::debug!("tick took too long");
+                let tick_events = if self.tick == tick {
+                    events.take().unwrap_or_default()
+                } else {
+                    Vec::new()
+                };
+                let time = start + self.tick_length.mul_f64(self.tick as f64);
+                span!("tick", tick = self.tick);
+                self.run_tick(tick_events, time);
             }
+        }
+    }
+
+    /// Runs the listener for a single tick and publishes the snapshot it returns.
+    /// Returns how long the listener ran.
+    fn run_tick(&mut self, events: Vec<E>, tick_time: Instant) -> Duration {
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.record(self.tick, &events);
+        }
+
+        let measured_dt = self.last_tick_time.map_or(self.tick_length, |last| {
+            tick_time.saturating_duration_since(last)
+        });
+        self.last_tick_time = Some(tick_time);
+        let dt = match self.timestep {
+            TimestepMode::Fixed => self.tick_length,
+            TimestepMode::Variable => measured_dt,
+            TimestepMode::SemiFixed { min, max } => measured_dt.max(min).min(max),
+        } / self.substeps;
+        let mut events = Some(events);
+        let mut published: Option<S> = None;
+        let mut listener_time = Duration::ZERO;
+        for substep in 0..self.substeps {
+            let time = tick_time + dt * substep;
+            let control = self.control.with_tick_info(Some(TickInfo {
+                tick: self.tick,
+                substep,
+                time,
+                nominal_dt: self.tick_length,
+                measured_dt,
+                wall_time: Instant::now(),
+                time_scale: self.control.time_scale(),
+            }));
+
+            let events = events.take().unwrap_or_default();
+            span!("listener", tick = self.tick, substep);
+            let listener_start = Instant::now();
+            let result = (self.listener)(dt.as_secs_f32(), events, control, time);
+            listener_time += listener_start.elapsed();
+            let Ok(snapshot) = result else {
+                continue;
+            };
+            published = Some(match (published, self.merge_substeps.as_mut()) {
+                (Some(merged), Some(merge)) => merge(merged, snapshot),
+                _ => snapshot,
+            });
+        }
+
+        if let Some(snapshot) = published {
+            span!("publish_snapshot", tick = self.tick);
+            let mut tick_wlock = self.snapshots.write().unwrap();
+            (*tick_wlock).update(snapshot);
+            // Drop the write lock so the read lock can be acquired.
+            drop(tick_wlock);
+            #[cfg(feature = "async")]
+            self.watch.publish();
+        }
+
+        self.tick += 1;
+        listener_time
+    }
+}
 
-            log::debug!("actual tick length {:?}", tick_time.elapsed());
+/// Returns the index of the first tick that starts at or after `time`, or `next_tick` if the loop has not started yet.
+fn tick_at(
+    start_time: Option<Instant>,
+    tick_length: Duration,
+    next_tick: u64,
+    time: Instant,
+) -> u64 {
+    match start_time {
+        Some(start_time) => {
+            let ticks = time.saturating_duration_since(start_time).as_secs_f64()
+                / tick_length.as_secs_f64();
+            ticks.ceil() as u64
         }
+        None => next_tick,
     }
 }
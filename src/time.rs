@@ -0,0 +1,203 @@
+//! Contains [`TickTime`], a serializable representation of when a tick happened.
+//! [`Instant`] is opaque and only meaningful inside the process that created it, so it can't be saved or sent to another machine.
+//! A [`TickTime`] instead stores the tick index and the time since a reference point, such as the start of a recording.
+//!
+//! [`SimClock`] is the clock the tick loop runs on. It stops while the loop is paused and can run faster or slower than the wall clock,
+//! so the time between snapshots always matches the simulated time between them.
+//!
+//! With the `serde` feature, `serde_instant` can also be used to serialize `Instant` fields directly, for example the time stored in a snapshot.
+
+use std::{
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+/// Returns the reference instant of this process. It is fixed the first time it is requested.
+pub fn epoch() -> Instant {
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    *EPOCH.get_or_init(Instant::now)
+}
+
+/// The time of a tick, stored as the tick index and the time since a reference instant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TickTime {
+    pub tick: u64,
+    /// The time since the reference instant.
+    pub offset: Duration,
+}
+
+impl TickTime {
+    pub fn new(tick: u64, offset: Duration) -> Self {
+        TickTime { tick, offset }
+    }
+
+    /// Creates a tick time from an instant, measured from `reference`.
+    /// Instants before `reference` are clamped to it.
+    pub fn from_instant(tick: u64, instant: Instant, reference: Instant) -> Self {
+        TickTime::new(tick, instant.saturating_duration_since(reference))
+    }
+
+    /// Converts the tick time back to an instant, measured from `reference`.
+    /// Loading a saved tick time relative to a new reference keeps the spacing between ticks intact.
+    pub fn to_instant(self, reference: Instant) -> Instant {
+        reference + self.offset
+    }
+}
+
+/// A clock that runs at a multiple of the speed of the wall clock, and stands still while the tick loop is paused.
+/// The tick loop stamps every tick with the time on this clock, so interpolate with [`SimClock::elapsed`] instead of [`Instant::elapsed`]
+/// to continue smoothly after a pause or a change of time scale.
+#[derive(Debug, Clone)]
+pub struct SimClock {
+    inner: Arc<Mutex<ClockState>>,
+}
+
+#[derive(Debug)]
+struct ClockState {
+    /// A wall clock instant, and the time the clock showed at it.
+    wall_anchor: Instant,
+    anchor: Instant,
+    scale: f64,
+    paused: bool,
+    /// Counts pauses, resumes and changes of scale.
+    changes: u64,
+}
+
+impl ClockState {
+    fn time_at(&self, instant: Instant) -> Instant {
+        if self.paused {
+            return self.anchor;
+        }
+        if instant >= self.wall_anchor {
+            self.anchor + (instant - self.wall_anchor).mul_f64(self.scale)
+        } else {
+            let before = (self.wall_anchor - instant).mul_f64(self.scale);
+            self.anchor.checked_sub(before).unwrap_or(self.anchor)
+        }
+    }
+
+    /// Moves the anchor to now, so the clock can change speed without jumping.
+    fn reanchor(&mut self) {
+        let now = Instant::now();
+        self.anchor = self.time_at(now);
+        self.wall_anchor = now;
+        self.changes += 1;
+    }
+}
+
+impl SimClock {
+    pub fn new() -> Self {
+        let now = Instant::now();
+        SimClock {
+            inner: Arc::new(Mutex::new(ClockState {
+                wall_anchor: now,
+                anchor: now,
+                scale: 1.0,
+                paused: false,
+                changes: 0,
+            })),
+        }
+    }
+
+    /// Returns the current time on this clock.
+    pub fn now(&self) -> Instant {
+        self.time_at(Instant::now())
+    }
+
+    /// Returns the time on this clock since `earlier`, which should be a time on this clock, such as a snapshot's time.
+    pub fn elapsed(&self, earlier: Instant) -> Duration {
+        self.now().saturating_duration_since(earlier)
+    }
+
+    /// Converts a wall clock instant to the time this clock showed at it, assuming the clock has not been paused or changed speed since.
+    pub fn time_at(&self, instant: Instant) -> Instant {
+        self.inner.lock().unwrap().time_at(instant)
+    }
+
+    /// Returns how long it takes on the wall clock for `duration` to pass on this clock, at the current scale.
+    pub fn wall_duration(&self, duration: Duration) -> Duration {
+        duration.div_f64(self.inner.lock().unwrap().scale)
+    }
+
+    /// Returns how many times faster than the wall clock this clock runs.
+    pub fn time_scale(&self) -> f64 {
+        self.inner.lock().unwrap().scale
+    }
+
+    /// Sets how many times faster than the wall clock this clock runs, without changing the current time.
+    /// Invalid scales, zero or below or not finite, are ignored. Use [`pause`](SimClock::pause) to stop the clock.
+    pub fn set_time_scale(&self, scale: f64) {
+        if !(scale.is_finite() && scale > 0.0) {
+            log::warn!("ignoring invalid time scale {}", scale);
+            return;
+        }
+        let mut state = self.inner.lock().unwrap();
+        state.reanchor();
+        state.scale = scale;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.inner.lock().unwrap().paused
+    }
+
+    /// Stops the clock. Does nothing if it is already paused.
+    pub fn pause(&self) {
+        let mut state = self.inner.lock().unwrap();
+        if !state.paused {
+            state.reanchor();
+            state.paused = true;
+        }
+    }
+
+    /// Starts the clock again from the time it was paused at.
+    pub fn resume(&self) {
+        let mut state = self.inner.lock().unwrap();
+        if state.paused {
+            state.reanchor();
+            state.paused = false;
+        }
+    }
+
+    /// Returns a counter that changes whenever the clock is paused, resumed or changes speed.
+    pub(crate) fn changes(&self) -> u64 {
+        self.inner.lock().unwrap().changes
+    }
+}
+
+impl Default for SimClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Serializes an [`Instant`] as the signed number of nanoseconds since the process [`epoch`], for use with `#[serde(with = "saunter::time::serde_instant")]`.
+/// Deserializing adds the saved offset to the epoch of the loading process,
+/// so instants saved together keep their spacing, which is all interpolation needs.
+#[cfg(feature = "serde")]
+pub mod serde_instant {
+    use super::epoch;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::time::{Duration, Instant};
+
+    pub fn serialize<S: Serializer>(instant: &Instant, serializer: S) -> Result<S::Ok, S::Error> {
+        let epoch = epoch();
+        let nanos = if *instant >= epoch {
+            (*instant - epoch).as_nanos() as i64
+        } else {
+            -((epoch - *instant).as_nanos() as i64)
+        };
+        nanos.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Instant, D::Error> {
+        let nanos = i64::deserialize(deserializer)?;
+        let offset = Duration::from_nanos(nanos.unsigned_abs());
+        let instant = if nanos >= 0 {
+            epoch().checked_add(offset)
+        } else {
+            epoch().checked_sub(offset)
+        };
+        instant.ok_or_else(|| serde::de::Error::custom("instant is out of range"))
+    }
+}
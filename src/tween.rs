@@ -0,0 +1,442 @@
+//! Contains tweens and the [`Timeline`] they are played on.
+//! Interpolating snapshots smooths the gap between two ticks, but authored animations (fades, camera moves, UI transitions) span many ticks.
+//! A [`Tween`] animates a value from `A` to `B` over a number of ticks, and a [`Sequence`] plays animations one after another.
+//! Both implement [`Animation`], which describes an animation without any playback state, so it can be sampled at any time.
+//!
+//! A [`Timeline`] plays several animations in parallel. It is advanced once per tick from inside the listener, so playback is deterministic,
+//! and runs completion callbacks as animations finish. [`Timeline::cursor`] captures the playback position in a [`TimelineCursor`],
+//! which can be stored in a snapshot so the renderer can sample the animations between ticks.
+
+use std::{
+    fmt::{Debug, Formatter},
+    sync::Arc,
+};
+
+use crate::{easing::Easing, interpolate::Interpolate};
+
+/// An animation that can be sampled at any time. Times are measured in ticks, and may be fractional to sample between ticks.
+pub trait Animation: Send + Sync {
+    type Output;
+
+    /// Returns the length of the animation in ticks, or `None` if it never ends.
+    fn duration(&self) -> Option<u32>;
+
+    /// Samples the animation `time` ticks after it started.
+    /// Times before the start give the first value, and times after the end give the last value.
+    fn sample_at(&self, time: f32) -> Self::Output;
+}
+
+/// How many times an animation plays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Repeat {
+    #[default]
+    Once,
+    /// Plays the animation this many times in total.
+    Times(u32),
+    Forever,
+}
+
+impl Repeat {
+    /// Returns the total number of plays, or `None` if the animation repeats forever.
+    fn plays(self) -> Option<u32> {
+        match self {
+            Repeat::Once => Some(1),
+            Repeat::Times(times) => Some(times.max(1)),
+            Repeat::Forever => None,
+        }
+    }
+}
+
+/// Splits `time` into the play it falls in and the progress through that play.
+/// Once every play has finished, this returns the end of the last play.
+fn cycle(time: f32, length: u32, repeat: Repeat) -> (u32, f32) {
+    if time <= 0.0 {
+        return (0, 0.0);
+    }
+    if length == 0 {
+        return (repeat.plays().unwrap_or(1) - 1, 1.0);
+    }
+
+    let length = length as f32;
+    let cycle = (time / length).floor() as u32;
+    match repeat.plays() {
+        Some(plays) if cycle >= plays => (plays - 1, 1.0),
+        _ => (cycle, (time - cycle as f32 * length) / length),
+    }
+}
+
+/// Animates a value from `from` to `to` over a number of ticks.
+#[derive(Clone)]
+pub struct Tween<T: Interpolate> {
+    pub from: T,
+    pub to: T,
+    /// The length of a single play in ticks.
+    pub duration: u32,
+    /// The number of ticks to wait before the first play.
+    pub delay: u32,
+    pub easing: Easing,
+    pub repeat: Repeat,
+    /// Whether every second play runs backwards, from `to` to `from`.
+    pub ping_pong: bool,
+}
+
+impl<T: Interpolate> Tween<T> {
+    pub fn new(from: T, to: T, duration: u32) -> Self {
+        Tween {
+            from,
+            to,
+            duration,
+            delay: 0,
+            easing: Easing::Linear,
+            repeat: Repeat::Once,
+            ping_pong: false,
+        }
+    }
+
+    pub fn with_easing(mut self, easing: impl Into<Easing>) -> Self {
+        self.easing = easing.into();
+        self
+    }
+
+    pub fn with_delay(mut self, delay: u32) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    pub fn with_repeat(mut self, repeat: Repeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Makes every second play run backwards.
+    pub fn with_ping_pong(mut self) -> Self {
+        self.ping_pong = true;
+        self
+    }
+}
+
+impl<T: Interpolate + Send + Sync> Animation for Tween<T> {
+    type Output = T;
+
+    fn duration(&self) -> Option<u32> {
+        self.repeat
+            .plays()
+            .map(|plays| self.delay + self.duration * plays)
+    }
+
+    fn sample_at(&self, time: f32) -> T {
+        let (cycle, progress) = cycle(time - self.delay as f32, self.duration, self.repeat);
+        let t = if self.ping_pong && cycle % 2 == 1 {
+            1.0 - progress
+        } else {
+            progress
+        };
+        T::interpolate(&self.from, &self.to, t, self.easing.as_fn())
+    }
+}
+
+impl<T: Interpolate + Debug> Debug for Tween<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tween")
+            .field("from", &self.from)
+            .field("to", &self.to)
+            .field("duration", &self.duration)
+            .field("delay", &self.delay)
+            .field("easing", &self.easing)
+            .field("repeat", &self.repeat)
+            .field("ping_pong", &self.ping_pong)
+            .finish()
+    }
+}
+
+type SharedAnimation<T> = Arc<dyn Animation<Output = T>>;
+
+enum Step<T> {
+    Play(SharedAnimation<T>),
+    Wait(u32),
+}
+
+// Clone is implemented by hand so that `T` doesn't need to be `Clone`.
+impl<T> Clone for Step<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Step::Play(animation) => Step::Play(animation.clone()),
+            Step::Wait(ticks) => Step::Wait(*ticks),
+        }
+    }
+}
+
+/// Plays animations one after another, optionally waiting between them.
+/// While waiting, the sequence holds the value the previous animation ended on.
+pub struct Sequence<T> {
+    steps: Vec<Step<T>>,
+    repeat: Repeat,
+}
+
+impl<T> Clone for Sequence<T> {
+    fn clone(&self) -> Self {
+        Sequence {
+            steps: self.steps.clone(),
+            repeat: self.repeat,
+        }
+    }
+}
+
+impl<T: 'static> Sequence<T> {
+    /// Creates a sequence that starts with `first`.
+    pub fn new(first: impl Animation<Output = T> + 'static) -> Self {
+        Sequence {
+            steps: vec![Step::Play(Arc::new(first))],
+            repeat: Repeat::Once,
+        }
+    }
+
+    /// Plays `animation` after the previous steps have finished.
+    /// Steps after an animation that never ends are never reached.
+    pub fn then(mut self, animation: impl Animation<Output = T> + 'static) -> Self {
+        self.steps.push(Step::Play(Arc::new(animation)));
+        self
+    }
+
+    /// Waits for `ticks` before the next step.
+    pub fn then_wait(mut self, ticks: u32) -> Self {
+        self.steps.push(Step::Wait(ticks));
+        self
+    }
+
+    pub fn with_repeat(mut self, repeat: Repeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Returns the length of one play of the sequence, or `None` if one of its animations never ends.
+    fn length(&self) -> Option<u32> {
+        self.steps.iter().try_fold(0, |length, step| match step {
+            Step::Play(animation) => animation.duration().map(|duration| length + duration),
+            Step::Wait(ticks) => Some(length + ticks),
+        })
+    }
+}
+
+impl<T: 'static> Animation for Sequence<T> {
+    type Output = T;
+
+    fn duration(&self) -> Option<u32> {
+        let length = self.length()?;
+        self.repeat.plays().map(|plays| length * plays)
+    }
+
+    fn sample_at(&self, time: f32) -> T {
+        let mut time = match self.length() {
+            Some(length) => {
+                let (_, progress) = cycle(time, length, self.repeat);
+                progress * length as f32
+            }
+            None => time,
+        };
+
+        // The animation whose end value is held while waiting.
+        let mut previous: Option<&SharedAnimation<T>> = None;
+        for (i, step) in self.steps.iter().enumerate() {
+            match step {
+                Step::Play(animation) => match animation.duration() {
+                    Some(duration) if time > duration as f32 && i + 1 < self.steps.len() => {
+                        time -= duration as f32;
+                        previous = Some(animation);
+                    }
+                    _ => return animation.sample_at(time),
+                },
+                Step::Wait(ticks) => {
+                    if time <= *ticks as f32 || i + 1 == self.steps.len() {
+                        break;
+                    }
+                    time -= *ticks as f32;
+                }
+            }
+        }
+
+        match previous {
+            Some(animation) => animation.sample_at(animation.duration().unwrap_or(0) as f32),
+            // Only reachable when the sequence is still waiting before its first animation.
+            None => self
+                .steps
+                .iter()
+                .find_map(|step| match step {
+                    Step::Play(animation) => Some(animation.sample_at(0.0)),
+                    Step::Wait(_) => None,
+                })
+                .expect("a sequence always contains an animation"),
+        }
+    }
+}
+
+/// Identifies an animation added to a [`Timeline`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TrackId(usize);
+
+struct Track<T> {
+    animation: SharedAnimation<T>,
+    start: u32,
+    on_complete: Option<Box<dyn FnMut() + Send>>,
+    completed: bool,
+}
+
+impl<T> Track<T> {
+    fn local_time(&self, time: f32) -> f32 {
+        let local = (time - self.start as f32).max(0.0);
+        match self.animation.duration() {
+            Some(duration) => local.min(duration as f32),
+            None => local,
+        }
+    }
+}
+
+/// Plays animations in parallel, advancing one tick at a time.
+/// Call [`tick`](Timeline::tick) once per tick in your listener, and use [`cursor`](Timeline::cursor) to hand the playback position to the renderer.
+pub struct Timeline<T> {
+    tick: u32,
+    tracks: Vec<Track<T>>,
+}
+
+impl<T: 'static> Timeline<T> {
+    pub fn new() -> Self {
+        Timeline {
+            tick: 0,
+            tracks: Vec::new(),
+        }
+    }
+
+    /// Adds an animation that starts at the current tick.
+    pub fn add(&mut self, animation: impl Animation<Output = T> + 'static) -> TrackId {
+        self.add_at(self.tick, animation)
+    }
+
+    /// Adds an animation that starts at the given tick of the timeline.
+    pub fn add_at(
+        &mut self,
+        start: u32,
+        animation: impl Animation<Output = T> + 'static,
+    ) -> TrackId {
+        self.tracks.push(Track {
+            animation: Arc::new(animation),
+            start,
+            on_complete: None,
+            completed: false,
+        });
+        TrackId(self.tracks.len() - 1)
+    }
+
+    /// Sets a callback that runs during the tick in which the animation finishes.
+    pub fn on_complete(&mut self, track: TrackId, callback: impl FnMut() + Send + 'static) {
+        self.tracks[track.0].on_complete = Some(Box::new(callback));
+    }
+
+    /// Advances the timeline by one tick, running the callbacks of animations that finished.
+    /// Callbacks run in the order the animations were added.
+    pub fn tick(&mut self) {
+        self.tick += 1;
+
+        for track in self.tracks.iter_mut().filter(|track| !track.completed) {
+            if let Some(duration) = track.animation.duration() {
+                if self.tick >= track.start + duration {
+                    track.completed = true;
+                    if let Some(callback) = track.on_complete.as_mut() {
+                        callback();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the number of ticks the timeline has been advanced.
+    pub fn current_tick(&self) -> u32 {
+        self.tick
+    }
+
+    /// Returns whether the animation has finished.
+    pub fn is_complete(&self, track: TrackId) -> bool {
+        self.tracks[track.0].completed
+    }
+
+    /// Returns whether every animation on the timeline has finished.
+    pub fn is_finished(&self) -> bool {
+        self.tracks.iter().all(|track| track.completed)
+    }
+
+    /// Samples an animation at the current tick, plus `alpha` of the way to the next tick.
+    pub fn sample(&self, track: TrackId, alpha: f32) -> T {
+        let track = &self.tracks[track.0];
+        track
+            .animation
+            .sample_at(track.local_time(self.tick as f32 + alpha))
+    }
+
+    /// Captures the current playback position, so the animations can be sampled outside of the tick loop.
+    pub fn cursor(&self) -> TimelineCursor<T> {
+        TimelineCursor {
+            time: self.tick as f32,
+            tracks: self
+                .tracks
+                .iter()
+                .map(|track| (track.animation.clone(), track.start))
+                .collect(),
+        }
+    }
+}
+
+impl<T: 'static> Default for Timeline<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A snapshot of a [`Timeline`]'s animations and playback position.
+/// Interpolating two cursors interpolates the playback position, so a cursor stored in a snapshot samples smoothly between ticks.
+pub struct TimelineCursor<T> {
+    time: f32,
+    tracks: Vec<(SharedAnimation<T>, u32)>,
+}
+
+impl<T> Clone for TimelineCursor<T> {
+    fn clone(&self) -> Self {
+        TimelineCursor {
+            time: self.time,
+            tracks: self.tracks.clone(),
+        }
+    }
+}
+
+impl<T> TimelineCursor<T> {
+    /// Returns the playback position in ticks.
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Samples an animation at the cursor's playback position.
+    pub fn sample(&self, track: TrackId) -> T {
+        let (animation, start) = &self.tracks[track.0];
+        let local = (self.time - *start as f32).max(0.0);
+        match animation.duration() {
+            Some(duration) => animation.sample_at(local.min(duration as f32)),
+            None => animation.sample_at(local),
+        }
+    }
+}
+
+impl<T> Interpolate for TimelineCursor<T> {
+    fn interpolate(start: &Self, end: &Self, t: f32, interpolation: impl Fn(f32) -> f32) -> Self {
+        TimelineCursor {
+            time: f32::interpolate(&start.time, &end.time, t, interpolation),
+            tracks: end.tracks.clone(),
+        }
+    }
+}
+
+impl<T> Debug for TimelineCursor<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TimelineCursor")
+            .field("time", &self.time)
+            .field("tracks", &self.tracks.len())
+            .finish()
+    }
+}
@@ -1,8 +1,26 @@
+use saunter::color::{Hsv, Oklab, Rgb};
 use saunter::derive::Interpolate;
+use saunter::easing::{CubicBezier, Easing, Spring};
+use saunter::error::SaunterError;
+use saunter::event::{self, Envelope, EventTarget, OverflowPolicy};
+use saunter::input::{InputEvent, InputState};
+use saunter::interpolate::*;
+use saunter::keyframe::{Keyframe, KeyframeTrack, SegmentMode, WrapMode};
+use saunter::replay::{RecordedTick, Recording};
+use saunter::rollback::Rollback;
+use saunter::scheduler::Scheduler;
 use saunter::snapshot::{Snapshot, Snapshots};
-use std::time::Instant;
+use saunter::stepper::FixedStepper;
+use saunter::tickloop::{TickInfo, TickLoop, TickLoopControl, TickLoopState, TimestepMode};
+use saunter::time::{SimClock, TickTime};
+use saunter::tween::{Animation, Repeat, Sequence, Timeline, TimelineCursor, Tween};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
 
-#[derive(Copy, Debug, Interpolate)]
+#[derive(Clone, Copy, Debug, Interpolate)]
 struct TestTick {
     tick: u8,
 }
@@ -21,18 +39,1705 @@ fn test_ticks_update() {
     };
 
     assert!(ticks.last_snapshot.is_none());
-    assert_eq!(ticks.new_snapshot.tick, 0);
+    assert_eq!(ticks.new_snapshot.unwrap().tick, 0);
 
     ticks.update(TestTick { tick: 1 });
     assert!(ticks.last_snapshot.is_some());
     assert_eq!(ticks.last_snapshot.unwrap().tick, 0);
-    assert_eq!(ticks.new_snapshot.tick, 1);
+    assert_eq!(ticks.new_snapshot.unwrap().tick, 1);
 
     ticks.update(TestTick { tick: 2 });
     assert_eq!(ticks.last_snapshot.unwrap().tick, 1);
-    assert_eq!(ticks.new_snapshot.tick, 2);
+    assert_eq!(ticks.new_snapshot.unwrap().tick, 2);
 
     ticks.update(TestTick { tick: 3 });
     assert_eq!(ticks.last_snapshot.unwrap().tick, 2);
-    assert_eq!(ticks.new_snapshot.tick, 3);
+    assert_eq!(ticks.new_snapshot.unwrap().tick, 3);
+}
+
+#[test]
+fn test_rgb_interpolates_in_linear_space() {
+    let red = Rgb::new(1.0, 0.0, 0.0);
+    let green = Rgb::new(0.0, 1.0, 0.0);
+
+    let mid = Rgb::interpolate(&red, &green, 0.5, linear);
+    // Naive sRGB lerp would give 0.5, which is much darker than the true blend.
+    assert!((mid.r - 0.7354).abs() < 0.001);
+    assert!((mid.g - 0.7354).abs() < 0.001);
+
+    assert_eq!(
+        Rgb::interpolate(&red, &green, 0.0, linear).to_rgb8(),
+        [255, 0, 0]
+    );
+    assert_eq!(
+        Rgb::interpolate(&red, &green, 1.0, linear).to_rgb8(),
+        [0, 255, 0]
+    );
+}
+
+#[test]
+fn test_hsv_takes_shortest_hue_path() {
+    let a = Hsv::new(350.0, 1.0, 1.0);
+    let b = Hsv::new(10.0, 1.0, 1.0);
+
+    assert!(Hsv::interpolate(&a, &b, 0.5, linear).h.abs() < 0.001);
+    assert!((Hsv::interpolate(&b, &a, 0.25, linear).h - 5.0).abs() < 0.001);
+}
+
+#[test]
+fn test_oklab_round_trip() {
+    let color = Rgb::from_rgb8(200, 100, 50);
+    let back = Rgb::from(Oklab::from(color));
+
+    assert_eq!(back.to_rgb8(), color.to_rgb8());
+    assert!((Oklab::from(Rgb::new(1.0, 1.0, 1.0)).l - 1.0).abs() < 0.001);
+}
+
+#[test]
+fn test_easing_names_round_trip() {
+    for name in [
+        "linear",
+        "ease_in_out_cubic",
+        "reverse(ease_in_quad)",
+        "chain(ease_in_sine, mirror(ease_out_expo))",
+        "scale(in_out(ease_in_circ), 0.5)",
+    ] {
+        let easing: Easing = name.parse().unwrap();
+        assert_eq!(easing.to_string(), name);
+    }
+
+    assert_eq!(
+        "ease_in_out_cubic".parse::<Easing>().unwrap(),
+        Easing::EaseInOutCubic
+    );
+    assert!("ease_sideways".parse::<Easing>().is_err());
+    assert!("chain(linear)".parse::<Easing>().is_err());
+    assert!("reverse(linear".parse::<Easing>().is_err());
+}
+
+#[test]
+fn test_easing_combinators() {
+    let quad = Easing::EaseInQuad;
+
+    assert_eq!(quad.ease(0.5), ease_in_quad(0.5));
+    assert_eq!(quad.clone().reverse().ease(0.25), ease_in_quad(0.75));
+    assert_eq!(quad.clone().mirror().ease(0.75), ease_in_quad(0.5));
+    assert_eq!(quad.clone().scale(2.0).ease(0.5), 0.5);
+    assert_eq!(
+        Easing::in_out_from(quad.clone()).ease(0.25),
+        ease_in_out_quad(0.25)
+    );
+    assert_eq!(
+        Easing::in_out_from(quad.clone()).ease(0.75),
+        ease_in_out_quad(0.75)
+    );
+
+    let chained = quad.chain(Easing::Linear);
+    assert_eq!(chained.ease(0.0), 0.0);
+    assert_eq!(chained.ease(0.5), 0.5);
+    assert_eq!(chained.ease(0.75), 0.75);
+
+    let custom = Easing::custom(|t| t * 0.5);
+    assert_eq!(custom.ease(1.0), 0.5);
+    assert_eq!(custom, custom.clone());
+    assert_ne!(custom, Easing::custom(|t| t * 0.5));
+}
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!(
+        (actual - expected).abs() < 1e-4,
+        "expected {}, got {}",
+        expected,
+        actual
+    );
+}
+
+#[test]
+fn test_elastic_and_bounce_easings() {
+    for f in [
+        ease_in_elastic,
+        ease_out_elastic,
+        ease_in_out_elastic,
+        ease_in_bounce,
+        ease_out_bounce,
+        ease_in_out_bounce,
+    ] {
+        assert_close(f(0.0), 0.0);
+        assert_close(f(1.0), 1.0);
+    }
+
+    assert_close(ease_in_elastic(0.5), -0.015625);
+    assert_close(ease_out_elastic(0.5), 1.015625);
+    assert_close(ease_in_out_elastic(0.5), 0.5);
+    assert_close(ease_out_bounce(0.5), 0.765625);
+    assert_close(ease_in_bounce(0.5), 0.234375);
+    assert_close(ease_in_out_bounce(0.5), 0.5);
+}
+
+#[test]
+fn test_parameterized_easings() {
+    let four_steps = steps(4);
+    assert_close(four_steps(0.0), 0.0);
+    assert_close(four_steps(0.24), 0.0);
+    assert_close(four_steps(0.3), 0.25);
+    assert_close(four_steps(0.99), 0.75);
+    assert_close(four_steps(1.0), 1.0);
+
+    for t in [0.0, 0.25, 0.5, 0.75, 1.0] {
+        assert_close(back(BACK_OVERSHOOT)(t), ease_in_back(t));
+        assert_close(back(0.0)(t), ease_in_cubic(t));
+        assert_close(power(2.0)(t), ease_in_quad(t));
+        assert_close(elastic(1.0, 0.3)(t), ease_in_elastic(t));
+    }
+    assert_close(power(0.5)(0.25), 0.5);
+
+    let stretched = elastic(2.0, 0.5);
+    assert_close(stretched(0.0), 0.0);
+    assert_close(stretched(1.0), 1.0);
+
+    assert_eq!(
+        "elastic(2, 0.5)".parse::<Easing>().unwrap(),
+        Easing::Elastic {
+            amplitude: 2.0,
+            period: 0.5
+        }
+    );
+    assert_eq!(Easing::Steps(4).to_string(), "steps(4)");
+    assert_close(
+        Easing::out_from(Easing::Back(2.0)).ease(0.5),
+        1.0 - back(2.0)(0.5),
+    );
+}
+
+#[test]
+fn test_cubic_bezier_easing() {
+    let linear_curve = CubicBezier::new(0.0, 0.0, 1.0, 1.0);
+    for t in [0.0, 0.1, 0.5, 0.9, 1.0] {
+        assert_close(linear_curve.ease(t), t);
+    }
+
+    assert_close(CubicBezier::EASE_IN_OUT.ease(0.5), 0.5);
+    assert_close(CubicBezier::EASE.ease(0.5), 0.8024);
+    assert_close(CubicBezier::EASE.ease(0.0), 0.0);
+    assert_close(CubicBezier::EASE.ease(1.0), 1.0);
+
+    // Steep curves where Newton's method stalls still solve accurately.
+    let steep = CubicBezier::new(1.0, 0.0, 0.0, 1.0);
+    assert_close(steep.ease(0.5), 0.5);
+    assert!(steep.ease(0.49) < 0.5 && steep.ease(0.51) > 0.5);
+
+    let easing: Easing = "cubic_bezier(0.25, 0.1, 0.25, 1)".parse().unwrap();
+    assert_eq!(easing, Easing::from(CubicBezier::EASE));
+}
+
+#[test]
+fn test_spring_easing() {
+    let critical = Spring::critically_damped(2.0);
+    let mut last = 0.0;
+    for i in 0..=100 {
+        let value = critical.ease(i as f32 / 100.0);
+        assert!(value >= last && value <= 1.0);
+        last = value;
+    }
+    assert_close(critical.ease(0.0), 0.0);
+    assert_eq!(critical.ease(1.0), 1.0);
+
+    let bouncy = Spring::new(0.3, 3.0);
+    assert!((0..=100).any(|i| bouncy.ease(i as f32 / 100.0) > 1.05));
+    assert_eq!(bouncy.ease(1.0), 1.0);
+
+    let still = Spring::new(0.5, 0.0);
+    assert_eq!(still.ease(0.5), 0.0);
+    assert_eq!(still.ease(1.0), 1.0);
+
+    let overdamped = Spring::new(2.0, 2.0);
+    assert!(overdamped.ease(0.2) > 0.0);
+    assert!(overdamped.ease(0.2) < critical.ease(0.2));
+
+    let physical = Spring::from_physical(1.0, 100.0, 20.0, 1.0);
+    assert_close(physical.damping_ratio, 1.0);
+
+    let interpolated = f32::interpolate(&0.0, &10.0, 0.5, critical.as_fn());
+    assert_close(interpolated, critical.ease(0.5) * 10.0);
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum EasingKind {
+    In,
+    Out,
+    InOut,
+}
+
+type EasingFn = fn(f32) -> f32;
+
+/// Every fixed easing function, its kind, and whether it stays monotonic (no overshoot or bounce).
+const EASINGS: &[(&str, EasingFn, EasingKind, bool)] = &[
+    ("linear", linear, EasingKind::InOut, true),
+    ("ease_in_sine", ease_in_sine, EasingKind::In, true),
+    ("ease_out_sine", ease_out_sine, EasingKind::Out, true),
+    (
+        "ease_in_out_sine",
+        ease_in_out_sine,
+        EasingKind::InOut,
+        true,
+    ),
+    ("ease_in_quad", ease_in_quad, EasingKind::In, true),
+    ("ease_out_quad", ease_out_quad, EasingKind::Out, true),
+    (
+        "ease_in_out_quad",
+        ease_in_out_quad,
+        EasingKind::InOut,
+        true,
+    ),
+    ("ease_in_cubic", ease_in_cubic, EasingKind::In, true),
+    ("ease_out_cubic", ease_out_cubic, EasingKind::Out, true),
+    (
+        "ease_in_out_cubic",
+        ease_in_out_cubic,
+        EasingKind::InOut,
+        true,
+    ),
+    ("ease_in_quart", ease_in_quart, EasingKind::In, true),
+    ("ease_out_quart", ease_out_quart, EasingKind::Out, true),
+    (
+        "ease_in_out_quart",
+        ease_in_out_quart,
+        EasingKind::InOut,
+        true,
+    ),
+    ("ease_in_quint", ease_in_quint, EasingKind::In, true),
+    ("ease_out_quint", ease_out_quint, EasingKind::Out, true),
+    (
+        "ease_in_out_quint",
+        ease_in_out_quint,
+        EasingKind::InOut,
+        true,
+    ),
+    ("ease_in_expo", ease_in_expo, EasingKind::In, true),
+    ("ease_out_expo", ease_out_expo, EasingKind::Out, true),
+    (
+        "ease_in_out_expo",
+        ease_in_out_expo,
+        EasingKind::InOut,
+        true,
+    ),
+    ("ease_in_circ", ease_in_circ, EasingKind::In, true),
+    ("ease_out_circ", ease_out_circ, EasingKind::Out, true),
+    (
+        "ease_in_out_circ",
+        ease_in_out_circ,
+        EasingKind::InOut,
+        true,
+    ),
+    ("ease_in_back", ease_in_back, EasingKind::In, false),
+    ("ease_out_back", ease_out_back, EasingKind::Out, false),
+    (
+        "ease_in_out_back",
+        ease_in_out_back,
+        EasingKind::InOut,
+        false,
+    ),
+    ("ease_in_elastic", ease_in_elastic, EasingKind::In, false),
+    ("ease_out_elastic", ease_out_elastic, EasingKind::Out, false),
+    (
+        "ease_in_out_elastic",
+        ease_in_out_elastic,
+        EasingKind::InOut,
+        false,
+    ),
+    ("ease_in_bounce", ease_in_bounce, EasingKind::In, false),
+    ("ease_out_bounce", ease_out_bounce, EasingKind::Out, false),
+    (
+        "ease_in_out_bounce",
+        ease_in_out_bounce,
+        EasingKind::InOut,
+        false,
+    ),
+];
+
+/// Reference values from the standard Penner equations.
+const REFERENCE_VALUES: &[(EasingFn, f32, f32)] = &[
+    (ease_in_sine, 0.5, 0.292893),
+    (ease_out_sine, 0.5, std::f32::consts::FRAC_1_SQRT_2),
+    (ease_in_out_sine, 0.25, 0.146447),
+    (ease_in_out_sine, 0.75, 0.853553),
+    (ease_in_out_quad, 0.25, 0.125),
+    (ease_in_out_cubic, 0.75, 0.9375),
+    (ease_in_out_quart, 0.25, 0.03125),
+    (ease_in_out_quint, 0.75, 0.984375),
+    (ease_in_expo, 0.5, 0.03125),
+    (ease_in_out_expo, 0.25, 0.015625),
+    (ease_in_circ, 0.5, 0.133975),
+    (ease_in_back, 0.5, -0.087698),
+    (ease_out_back, 0.5, 1.087698),
+    (ease_in_out_back, 0.25, -0.099682),
+    (ease_in_out_back, 0.75, 1.099682),
+];
+
+#[test]
+fn test_easing_endpoints() {
+    for (name, f, _, _) in EASINGS {
+        assert!(f(0.0).abs() < 1e-4, "{} does not start at 0", name);
+        assert!((f(1.0) - 1.0).abs() < 1e-4, "{} does not end at 1", name);
+    }
+}
+
+#[test]
+fn test_easing_monotonicity() {
+    for (name, f, _, monotonic) in EASINGS {
+        if !monotonic {
+            continue;
+        }
+        let mut last = f(0.0);
+        for i in 1..=1000 {
+            let value = f(i as f32 / 1000.0);
+            assert!(value >= last - 1e-6, "{} is not monotonic", name);
+            last = value;
+        }
+    }
+}
+
+#[test]
+fn test_easing_symmetry() {
+    for (name, f, kind, _) in EASINGS {
+        for i in 0..=100 {
+            let t = i as f32 / 100.0;
+            match kind {
+                EasingKind::InOut => assert!(
+                    (f(t) + f(1.0 - t) - 1.0).abs() < 1e-4,
+                    "{} is not symmetric at {}",
+                    name,
+                    t
+                ),
+                EasingKind::Out => {
+                    // Every out easing is its in counterpart flipped.
+                    let in_name = name.replace("out", "in");
+                    let (_, ease_in, _, _) = EASINGS.iter().find(|(n, ..)| *n == in_name).unwrap();
+                    assert!(
+                        (f(t) - (1.0 - ease_in(1.0 - t))).abs() < 1e-4,
+                        "{} is not the flipped {} at {}",
+                        name,
+                        in_name,
+                        t
+                    );
+                }
+                EasingKind::In => {}
+            }
+        }
+    }
+}
+
+#[test]
+fn test_easing_reference_values() {
+    for (f, t, expected) in REFERENCE_VALUES {
+        assert_close(f(*t), *expected);
+    }
+}
+
+#[test]
+fn test_easing_enum_matches_functions() {
+    for (name, f, _, _) in EASINGS {
+        let easing: Easing = name.parse().unwrap();
+        assert_eq!(easing.to_string(), *name);
+        for i in 0..=10 {
+            let t = i as f32 / 10.0;
+            assert_eq!(easing.ease(t), f(t), "{} differs at {}", name, t);
+        }
+    }
+}
+
+#[test]
+fn test_tween_sampling() {
+    let tween = Tween::new(0.0f32, 10.0, 10).with_delay(5);
+
+    assert_eq!(tween.duration(), Some(15));
+    assert_close(tween.sample_at(0.0), 0.0);
+    assert_close(tween.sample_at(5.0), 0.0);
+    assert_close(tween.sample_at(7.5), 2.5);
+    assert_close(tween.sample_at(15.0), 10.0);
+    assert_close(tween.sample_at(100.0), 10.0);
+
+    let eased = Tween::new(0.0f32, 1.0, 4).with_easing(Easing::EaseInQuad);
+    assert_close(eased.sample_at(2.0), 0.25);
+
+    let ping_pong = Tween::new(0.0f32, 4.0, 4)
+        .with_repeat(Repeat::Times(3))
+        .with_ping_pong();
+    assert_eq!(ping_pong.duration(), Some(12));
+    assert_close(ping_pong.sample_at(3.0), 3.0);
+    assert_close(ping_pong.sample_at(5.0), 3.0);
+    assert_close(ping_pong.sample_at(9.0), 1.0);
+    assert_close(ping_pong.sample_at(12.0), 4.0);
+
+    let looping = Tween::new(0.0f32, 4.0, 4).with_repeat(Repeat::Forever);
+    assert_eq!(looping.duration(), None);
+    assert_close(looping.sample_at(41.0), 1.0);
+}
+
+#[test]
+fn test_sequence_sampling() {
+    let sequence = Sequence::new(Tween::new(0.0f32, 1.0, 2))
+        .then_wait(2)
+        .then(Tween::new(1.0, 3.0, 2));
+
+    assert_eq!(sequence.duration(), Some(6));
+    assert_close(sequence.sample_at(1.0), 0.5);
+    assert_close(sequence.sample_at(3.0), 1.0);
+    assert_close(sequence.sample_at(5.0), 2.0);
+    assert_close(sequence.sample_at(10.0), 3.0);
+
+    let repeated = sequence.clone().with_repeat(Repeat::Times(2));
+    assert_eq!(repeated.duration(), Some(12));
+    assert_close(repeated.sample_at(7.0), 0.5);
+
+    let delayed = Sequence::new(Tween::new(0.0f32, 1.0, 2));
+    let delayed = Sequence::new(Tween::new(5.0f32, 5.0, 0))
+        .then_wait(3)
+        .then(delayed);
+    assert_close(delayed.sample_at(1.0), 5.0);
+    assert_close(delayed.sample_at(4.0), 0.5);
+}
+
+#[test]
+fn test_timeline_playback() {
+    let completed = Arc::new(AtomicU32::new(0));
+
+    let mut timeline = Timeline::new();
+    let fade = timeline.add(Tween::new(0.0f32, 1.0, 4));
+    let slide = timeline.add_at(2, Tween::new(10.0f32, 20.0, 2));
+
+    let counter = completed.clone();
+    timeline.on_complete(fade, move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+    });
+
+    assert_close(timeline.sample(fade, 0.5), 0.125);
+    assert_close(timeline.sample(slide, 0.5), 10.0);
+
+    for _ in 0..3 {
+        timeline.tick();
+    }
+    assert_close(timeline.sample(slide, 0.5), 17.5);
+    assert_eq!(completed.load(Ordering::SeqCst), 0);
+
+    let before = timeline.cursor();
+    timeline.tick();
+    let after = timeline.cursor();
+    assert_eq!(completed.load(Ordering::SeqCst), 1);
+    assert!(timeline.is_finished());
+
+    let between = TimelineCursor::interpolate(&before, &after, 0.5, linear);
+    assert_close(between.time(), 3.5);
+    assert_close(between.sample(fade), 0.875);
+    assert_close(between.sample(slide), 17.5);
+
+    timeline.tick();
+    assert_eq!(completed.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_keyframe_track_sampling() {
+    let track = KeyframeTrack::new(vec![
+        Keyframe::new(4.0, 0.0f32),
+        Keyframe::new(0.0, 10.0).with_mode(SegmentMode::Step),
+        Keyframe::new(2.0, 20.0).with_easing(Easing::EaseInQuad),
+    ]);
+
+    assert!(KeyframeTrack::<f32>::new(Vec::new()).sample(0.0).is_none());
+    assert_eq!(track.start_time(), Some(0.0));
+    assert_eq!(track.end_time(), Some(4.0));
+
+    assert_close(track.sample(-1.0).unwrap(), 10.0);
+    assert_close(track.sample(1.9).unwrap(), 10.0);
+    assert_close(track.sample(2.0).unwrap(), 20.0);
+    assert_close(track.sample(3.0).unwrap(), 15.0);
+    assert_close(track.sample(4.0).unwrap(), 0.0);
+    assert_close(track.sample(9.0).unwrap(), 0.0);
+
+    let mut looping = track.clone().with_wrap(WrapMode::Loop);
+    assert_close(looping.sample(7.0).unwrap(), 15.0);
+    looping.insert(Keyframe::new(8.0, 0.0));
+    assert_eq!(looping.end_time(), Some(8.0));
+
+    let ping_pong = track.with_wrap(WrapMode::PingPong);
+    assert_close(ping_pong.sample(5.0).unwrap(), 15.0);
+    assert_close(ping_pong.sample(8.5).unwrap(), 10.0);
+}
+
+#[test]
+fn test_keyframe_track_cubic_segments() {
+    let cubic = |time: f32, value: f32| Keyframe::new(time, value).with_mode(SegmentMode::Cubic);
+
+    // A straight line of evenly spaced keys stays straight.
+    let straight = KeyframeTrack::new(vec![cubic(0.0, 0.0), cubic(1.0, 1.0), cubic(2.0, 2.0)]);
+    for t in [0.0, 0.25, 0.5, 1.5, 2.0] {
+        assert_close(straight.sample(t).unwrap(), t);
+    }
+
+    // A curve passes through every key and eases smoothly through the peaks.
+    let curve = KeyframeTrack::new(vec![
+        cubic(0.0, 0.0),
+        cubic(1.0, 1.0),
+        cubic(2.0, 0.0),
+        cubic(3.0, 1.0),
+    ]);
+    assert_close(curve.sample(1.0).unwrap(), 1.0);
+    assert_close(curve.sample(2.0).unwrap(), 0.0);
+    assert_close(curve.sample(1.5).unwrap(), 0.5);
+    assert!(curve.sample(0.9).unwrap() > 0.9);
+    assert!(curve.sample(0.9).unwrap() < 1.0);
+    assert!(curve.sample(1.1).unwrap() > 0.9);
+    assert!(curve.sample(1.1).unwrap() < 1.0);
+
+    assert_eq!(curve.duration(), Some(3));
+    assert_close(curve.sample_at(1.0), 1.0);
+}
+
+#[derive(Clone, Copy, Debug, Interpolate)]
+struct SumSnapshot {
+    sum: u64,
+}
+
+impl Snapshot for SumSnapshot {
+    fn get_time(&self) -> &Instant {
+        unimplemented!()
+    }
+}
+
+/// A deterministic listener that folds every event batch into a running hash, and logs the hash of every tick.
+fn hashing_listener(
+    outputs: Arc<Mutex<Vec<u64>>>,
+) -> impl FnMut(f32, Vec<u64>, TickLoopControl, Instant) -> Result<SumSnapshot, SaunterError> {
+    let mut sum = 0u64;
+    move |_dt, events, _ctrl, _time| {
+        sum = sum
+            .wrapping_mul(31)
+            .wrapping_add(events.iter().sum::<u64>());
+        outputs.lock().unwrap().push(sum);
+        Ok(SumSnapshot { sum })
+    }
+}
+
+#[test]
+fn test_record_and_replay() {
+    let recording = Arc::new(Mutex::new(Recording::new()));
+    let live_outputs = Arc::new(Mutex::new(Vec::new()));
+
+    let (tick_loop, event_sender, mut ctrl, _) =
+        TickLoop::init(hashing_listener(live_outputs.clone()), 1000.0);
+    let mut tick_loop = tick_loop.with_recorder(recording.clone());
+    let handle = thread::spawn(move || tick_loop.start());
+
+    for event in 0..20 {
+        event_sender.send(event).unwrap();
+        thread::sleep(Duration::from_micros(700));
+    }
+    thread::sleep(Duration::from_millis(10));
+    ctrl.stop();
+    handle.join().unwrap();
+
+    let recording = recording.lock().unwrap().clone();
+    let live_outputs = live_outputs.lock().unwrap().clone();
+    assert_eq!(recording.len(), live_outputs.len());
+    assert_eq!(
+        recording
+            .ticks
+            .iter()
+            .flat_map(|tick| &tick.events)
+            .sum::<u64>(),
+        190
+    );
+
+    let replay_outputs = Arc::new(Mutex::new(Vec::new()));
+    let (mut replay_loop, _, _, snapshots) =
+        TickLoop::init(hashing_listener(replay_outputs.clone()), 1000.0);
+    replay_loop.replay(recording);
+
+    assert_eq!(*replay_outputs.lock().unwrap(), live_outputs);
+    assert_eq!(
+        snapshots.read().unwrap().new_snapshot.unwrap().sum,
+        *live_outputs.last().unwrap()
+    );
+}
+
+#[test]
+fn test_scheduled_events() {
+    let delivered = Arc::new(Mutex::new(Vec::new()));
+    let listener_delivered = delivered.clone();
+    let (mut tick_loop, event_sender, _, _) = TickLoop::init(
+        move |_dt, events: Vec<u64>, mut ctrl: TickLoopControl, _time| {
+            let mut delivered = listener_delivered.lock().unwrap();
+            delivered.push(events);
+            if delivered.len() == 5 {
+                ctrl.stop();
+            }
+            Ok(SumSnapshot { sum: 0 })
+        },
+        1000.0,
+    );
+
+    event_sender.send_at(3, 30).unwrap();
+    event_sender.send_at(1, 10).unwrap();
+    event_sender.send(0).unwrap();
+    event_sender.send_at(1, 11).unwrap();
+    event_sender
+        .send_at_time(Instant::now() - Duration::from_secs(1), 1)
+        .unwrap();
+    thread::spawn(move || tick_loop.start()).join().unwrap();
+
+    assert_eq!(
+        *delivered.lock().unwrap(),
+        vec![vec![0, 1], vec![10, 11], vec![], vec![30], vec![]]
+    );
+}
+
+#[test]
+fn test_bounded_event_channel() {
+    let next_tick = |_| 0;
+
+    let (sender, mut reciever) = event::bounded(2, OverflowPolicy::DropNewest);
+    (1..=4).for_each(|event| sender.send(event).unwrap());
+    assert_eq!(reciever.events_for(0, next_tick), vec![1, 2]);
+    assert_eq!(reciever.dropped(), 2);
+
+    let (sender, mut reciever) = event::bounded(2, OverflowPolicy::DropOldest);
+    (1..=4).for_each(|event| sender.send(event).unwrap());
+    assert_eq!(reciever.events_for(0, next_tick), vec![3, 4]);
+    assert_eq!(reciever.dropped(), 2);
+
+    // Only merges even numbers, so 5 is dropped.
+    let merge = |a: &u32, b: &u32| b.is_multiple_of(2).then_some(a + b);
+    let (sender, mut reciever) = event::bounded(2, OverflowPolicy::coalesce(merge));
+    [1, 2, 4, 5, 6]
+        .into_iter()
+        .for_each(|event| sender.send(event).unwrap());
+    assert_eq!(reciever.events_for(0, next_tick), vec![1, 12]);
+    assert_eq!(reciever.dropped(), 1);
+
+    // Events for different ticks are not merged, the one being sent is dropped instead.
+    let sum = |a: &u32, b: &u32| Some(a + b);
+    let (sender, mut reciever) = event::bounded(1, OverflowPolicy::coalesce(sum));
+    sender.send_at(100, 1).unwrap();
+    sender.send(2).unwrap();
+    sender.send_at(100, 4).unwrap();
+    assert_eq!(reciever.events_for(0, next_tick), Vec::<u32>::new());
+    assert_eq!(reciever.events_for(100, next_tick), vec![5]);
+    assert_eq!(reciever.dropped(), 1);
+
+    let (sender, mut reciever) = event::bounded(1, OverflowPolicy::Block);
+    let handle = thread::spawn(move || (1..=3).for_each(|event| sender.send(event).unwrap()));
+    let mut received = Vec::new();
+    while received.len() < 3 {
+        received.extend(reciever.events_for(0, next_tick));
+        thread::yield_now();
+    }
+    handle.join().unwrap();
+    assert_eq!(received, vec![1, 2, 3]);
+    assert_eq!(reciever.dropped(), 0);
+
+    // A capacity of 0 holds one event, instead of blocking or dropping every event.
+    let (sender, mut reciever) = event::bounded(0, OverflowPolicy::DropOldest);
+    sender.send(1).unwrap();
+    sender.send(2).unwrap();
+    assert_eq!(reciever.events_for(0, next_tick), vec![2]);
+    assert_eq!(reciever.dropped(), 1);
+    let (sender, mut reciever) = event::bounded(0, OverflowPolicy::Block);
+    sender.send(1).unwrap();
+    assert_eq!(reciever.events_for(0, next_tick), vec![1]);
+
+    let (tick_loop, sender, ctrl, _) = TickLoop::init(hashing_listener(Default::default()), 60.0);
+    let tick_loop = tick_loop.with_event_capacity(0, OverflowPolicy::DropNewest);
+    sender.send(1).unwrap();
+    sender.send(2).unwrap();
+    assert_eq!(ctrl.dropped_events(), 1);
+
+    drop(tick_loop);
+    assert!(sender.send(3).is_err());
+}
+
+#[test]
+fn test_event_reducer() {
+    let delivered = Arc::new(Mutex::new(Vec::new()));
+    let listener_delivered = delivered.clone();
+    let (tick_loop, event_sender, _, _) = TickLoop::init(
+        move |_dt, events: Vec<u64>, mut ctrl: TickLoopControl, _time| {
+            listener_delivered.lock().unwrap().push(events);
+            ctrl.stop();
+            Ok(SumSnapshot { sum: 0 })
+        },
+        1000.0,
+    );
+    // Drops zeroes and folds everything else into a single sum.
+    let mut tick_loop = tick_loop.with_reducer(|events: Vec<u64>| {
+        let sum: u64 = events.into_iter().filter(|event| *event != 0).sum();
+        vec![sum]
+    });
+
+    for event in [3, 0, 4, 0, 5] {
+        event_sender.send(event).unwrap();
+    }
+    tick_loop.start();
+
+    assert_eq!(*delivered.lock().unwrap(), vec![vec![12]]);
+}
+
+#[test]
+fn test_outbound_messages() {
+    let (tick_loop, _, ctrl, _) = TickLoop::init(
+        move |_dt, _events: Vec<()>, mut ctrl: TickLoopControl, _time| {
+            ctrl.send_message("tick".to_string())?;
+            ctrl.send_message(1u32)?;
+            assert!(matches!(
+                ctrl.send_message(0u8),
+                Err(SaunterError::MessageNotDelivered)
+            ));
+            ctrl.stop();
+            Ok(SumSnapshot { sum: 0 })
+        },
+        1000.0,
+    );
+    let (mut tick_loop, messages) = tick_loop.with_messages::<String>();
+    let numbers = ctrl.messages::<u32>().unwrap();
+    assert!(matches!(
+        ctrl.messages::<String>(),
+        Err(SaunterError::MessageChannelExists)
+    ));
+    tick_loop.start();
+
+    assert_eq!(messages.try_iter().collect::<Vec<_>>(), vec!["tick"]);
+    assert_eq!(numbers.try_iter().collect::<Vec<_>>(), vec![1]);
+
+    // Loops on a scheduler keep their own channels.
+    let mut scheduler = Scheduler::new(1000.0);
+    let mut receivers = Vec::new();
+    for id in 0..2u32 {
+        let (tick_loop, _, _, _) = TickLoop::init(
+            move |_dt, _events: Vec<()>, ctrl: TickLoopControl, _time| {
+                ctrl.send_message(id)?;
+                Ok(SumSnapshot { sum: 0 })
+            },
+            1.0,
+        );
+        let (tick_loop, reciever) = tick_loop.with_messages::<u32>();
+        scheduler.add_loop(tick_loop, 1);
+        receivers.push(reciever);
+    }
+    let mut ctrl = scheduler.control();
+    let handle = thread::spawn(move || scheduler.start());
+    thread::sleep(Duration::from_millis(20));
+    ctrl.stop();
+    handle.join().unwrap();
+    for (id, reciever) in receivers.iter().enumerate() {
+        let received: Vec<_> = reciever.try_iter().collect();
+        assert!(!received.is_empty());
+        assert!(received.iter().all(|&message| message == id as u32));
+    }
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn test_async_snapshots() {
+    let mut sum = 0;
+    let (tick_loop, event_sender, _, _) = TickLoop::init(
+        move |_dt, events: Vec<u64>, mut ctrl: TickLoopControl, _time| {
+            sum += events.iter().sum::<u64>();
+            if sum >= 10 {
+                ctrl.stop();
+            }
+            Ok(SumSnapshot { sum })
+        },
+        1000.0,
+    );
+    let mut tick_loop = tick_loop.with_event_capacity(1, OverflowPolicy::Block);
+    let mut handle = tick_loop.handle();
+
+    let runner = thread::spawn(move || {
+        pollster::block_on(tick_loop.run_async(|duration| async move { thread::sleep(duration) }))
+    });
+    let sender = thread::spawn(move || {
+        pollster::block_on(async {
+            for event in 1..=4 {
+                event_sender.send_async(event).await.unwrap();
+            }
+        })
+    });
+
+    let sums = pollster::block_on(async {
+        let mut sums = Vec::new();
+        while let Some(snapshot) = handle.next_snapshot().await {
+            sums.push(snapshot.sum);
+        }
+        sums
+    });
+    runner.join().unwrap();
+    sender.join().unwrap();
+
+    assert_eq!(sums.last(), Some(&10));
+    assert!(sums.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert_eq!(
+        pollster::block_on(handle.next_snapshot()).map(|s| s.sum),
+        None
+    );
+}
+
+#[test]
+fn test_observe_state() {
+    let mut ticks = 0;
+    let (mut tick_loop, _, ctrl, _) = TickLoop::init(
+        move |_dt, _events: Vec<()>, mut ctrl: TickLoopControl, _time| {
+            ticks += 1;
+            if ticks == 3 {
+                ctrl.stop();
+            }
+            Ok(SumSnapshot { sum: ticks })
+        },
+        1000.0,
+    );
+    let changes = Arc::new(Mutex::new(Vec::new()));
+    let callback_changes = changes.clone();
+    ctrl.on_state_change(move |state| callback_changes.lock().unwrap().push(state));
+
+    assert_eq!(ctrl.state(), TickLoopState::Running);
+    assert!(!ctrl.wait_for_state(TickLoopState::Stopped, Duration::from_millis(1)));
+
+    let handle = thread::spawn(move || tick_loop.start());
+    assert!(ctrl.wait_for_state(TickLoopState::Stopped, Duration::from_secs(5)));
+    handle.join().unwrap();
+
+    assert_eq!(ctrl.state(), TickLoopState::Stopped);
+    assert_eq!(*changes.lock().unwrap(), vec![TickLoopState::Stopped]);
+}
+
+#[test]
+fn test_responsive_pause_and_stop() {
+    let ticks = Arc::new(AtomicU32::new(0));
+    let listener_ticks = ticks.clone();
+    let (mut tick_loop, _, mut ctrl, _) = TickLoop::init(
+        move |_dt, _events: Vec<()>, _ctrl, _time| {
+            listener_ticks.fetch_add(1, Ordering::SeqCst);
+            Ok(SumSnapshot { sum: 0 })
+        },
+        1.0,
+    );
+    let handle = thread::spawn(move || tick_loop.start());
+    while ticks.load(Ordering::SeqCst) == 0 {
+        thread::yield_now();
+    }
+
+    // Pausing wakes the sleep between ticks, and stopping wakes the paused loop.
+    ctrl.pause();
+    thread::sleep(Duration::from_millis(20));
+    let stopped = Instant::now();
+    ctrl.stop();
+    handle.join().unwrap();
+    assert!(stopped.elapsed() < Duration::from_millis(500));
+    assert_eq!(ticks.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_sim_clock() {
+    let clock = SimClock::new();
+    let start = clock.now();
+    clock.pause();
+    let paused = clock.now();
+    thread::sleep(Duration::from_millis(20));
+    assert_eq!(clock.now(), paused);
+    assert!(clock.is_paused());
+
+    clock.resume();
+    assert!(clock.elapsed(start) < Duration::from_millis(20));
+}
+
+#[test]
+fn test_time_scale() {
+    let clock = SimClock::new();
+    clock.set_time_scale(4.0);
+    clock.set_time_scale(0.0);
+    let start = clock.now();
+    thread::sleep(Duration::from_millis(20));
+    assert!(clock.elapsed(start) >= Duration::from_millis(75));
+    assert_eq!(
+        clock.wall_duration(Duration::from_millis(8)),
+        Duration::from_millis(2)
+    );
+
+    let infos = Arc::new(Mutex::new(Vec::new()));
+    let listener_infos = infos.clone();
+    let (mut tick_loop, _, ctrl, _) = TickLoop::init(
+        move |dt, _events: Vec<()>, ctrl: TickLoopControl, _time| {
+            assert_eq!(dt, 0.01);
+            listener_infos
+                .lock()
+                .unwrap()
+                .push(*ctrl.tick_info().unwrap());
+            Ok(SumSnapshot { sum: 0 })
+        },
+        100.0,
+    );
+    ctrl.set_time_scale(4.0);
+    let handle = thread::spawn(move || tick_loop.start());
+    thread::sleep(Duration::from_millis(100));
+    ctrl.clone().stop();
+    handle.join().unwrap();
+
+    let infos = infos.lock().unwrap();
+    assert!(infos.len() >= 20);
+    for (tick, info) in infos.iter().enumerate() {
+        assert_eq!(info.tick, tick as u64);
+        assert_eq!(info.time_scale, 4.0);
+    }
+    let (first, last) = (infos.first().unwrap(), infos.last().unwrap());
+    assert!(last.time - first.time > (last.wall_time - first.wall_time) * 3);
+
+    let far = TickInfo {
+        tick: (1 << 40) + 2,
+        ..*last
+    };
+    let near = TickInfo {
+        tick: 1 << 40,
+        ..*last
+    };
+    assert_eq!(
+        TickInfo::interpolate(&near, &far, 0.5, linear).tick,
+        (1 << 40) + 1
+    );
+}
+
+#[derive(Clone, Copy, Debug, Interpolate)]
+struct TimedSnapshot {
+    time: Instant,
+    value: f32,
+}
+
+impl Snapshot for TimedSnapshot {
+    fn get_time(&self) -> &Instant {
+        &self.time
+    }
+}
+
+#[test]
+fn test_snapshot_alpha() {
+    let clock = SimClock::new();
+    clock.set_time_scale(4.0);
+    clock.pause();
+    let now = clock.now();
+
+    let mut snapshots = Snapshots::new();
+    snapshots.update(TimedSnapshot {
+        time: now - Duration::from_millis(20),
+        value: 0.0,
+    });
+    assert!(snapshots.alpha(&clock).is_err());
+    snapshots.update(TimedSnapshot {
+        time: now - Duration::from_millis(5),
+        value: 3.0,
+    });
+
+    // The snapshot times are on the scaled clock, so the wall clock must not matter.
+    thread::sleep(Duration::from_millis(10));
+    assert!((snapshots.alpha(&clock).unwrap() - 1.0 / 3.0).abs() < 0.001);
+    assert!((snapshots.interpolate_now(&clock, linear).unwrap().value - 1.0).abs() < 0.01);
+
+    clock.resume();
+    thread::sleep(Duration::from_millis(10));
+    assert_eq!(snapshots.alpha(&clock).unwrap(), 1.0);
+}
+
+#[test]
+fn test_interpolate_scaled_times() {
+    let clock = SimClock::new();
+    clock.set_time_scale(4.0);
+    let start = clock.now();
+    thread::sleep(Duration::from_millis(10));
+    // Runs ahead of the wall clock.
+    let end = clock.now();
+    assert!(end > Instant::now());
+
+    let mid = Instant::interpolate(&start, &end, 0.5, linear);
+    assert!(start <= mid && mid <= end);
+    assert!((mid - start).abs_diff((end - start) / 2) < Duration::from_micros(10));
+    assert_eq!(Instant::interpolate(&end, &start, 1.0, linear), start);
+    assert!(Instant::interpolate(&start, &end, 1.5, linear) > end);
+}
+
+#[test]
+fn test_pause_preserves_time() {
+    let times = Arc::new(Mutex::new(Vec::new()));
+    let listener_times = times.clone();
+    let (mut tick_loop, _, mut ctrl, _) = TickLoop::init(
+        move |_dt, _events: Vec<()>, _ctrl, time| {
+            listener_times.lock().unwrap().push(time);
+            Ok(SumSnapshot { sum: 0 })
+        },
+        20.0,
+    );
+    let handle = thread::spawn(move || tick_loop.start());
+    while times.lock().unwrap().len() < 2 {
+        thread::yield_now();
+    }
+
+    ctrl.pause();
+    let ticks_before_pause = times.lock().unwrap().len();
+    thread::sleep(Duration::from_millis(300));
+    let last_time = *times.lock().unwrap().last().unwrap();
+    assert!(ctrl.clock().elapsed(last_time) < Duration::from_millis(100));
+
+    // Resuming continues the wait for the next tick, instead of running the ticks the pause skipped.
+    ctrl.resume();
+    thread::sleep(Duration::from_millis(30));
+    assert!(times.lock().unwrap().len() <= ticks_before_pause + 1);
+    while times.lock().unwrap().len() < ticks_before_pause + 2 {
+        thread::yield_now();
+    }
+    ctrl.stop();
+    handle.join().unwrap();
+
+    let times = times.lock().unwrap();
+    for pair in times.windows(2) {
+        assert!(pair[1] - pair[0] < Duration::from_millis(150));
+    }
+}
+
+/// Runs three loops at full, half and quarter rate until the full rate loop has run 8 times,
+/// and returns the order they ran in and the threads they ran on.
+fn run_scheduler(threads: usize) -> (Vec<(char, f32)>, HashSet<thread::ThreadId>) {
+    let order = Arc::new(Mutex::new(Vec::new()));
+    let thread_ids = Arc::new(Mutex::new(HashSet::new()));
+    let mut scheduler = Scheduler::new(1000.0);
+    let mut snapshots = Vec::new();
+    for (name, divisor) in [('a', 1), ('b', 2), ('c', 4)] {
+        let order = order.clone();
+        let thread_ids = thread_ids.clone();
+        let mut ticks = 0;
+        let (_, loop_snapshots) = scheduler.add(
+            move |dt, _events: Vec<()>, mut ctrl: TickLoopControl, _time| {
+                ticks += 1;
+                order.lock().unwrap().push((name, dt));
+                thread_ids.lock().unwrap().insert(thread::current().id());
+                if name == 'a' && ticks == 8 {
+                    ctrl.stop();
+                }
+                Ok(SumSnapshot { sum: ticks })
+            },
+            divisor,
+        );
+        snapshots.push(loop_snapshots);
+    }
+    scheduler.start_parallel(threads);
+
+    let sums: Vec<_> = snapshots
+        .iter()
+        .map(|snapshots| snapshots.read().unwrap().new_snapshot.unwrap().sum)
+        .collect();
+    assert_eq!(sums, vec![8, 4, 2]);
+    let order = order.lock().unwrap().clone();
+    let thread_ids = thread_ids.lock().unwrap().clone();
+    (order, thread_ids)
+}
+
+#[test]
+fn test_substeps() {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let listener_calls = calls.clone();
+    let (tick_loop, event_sender, ctrl, snapshots) = TickLoop::init(
+        move |dt, events: Vec<u64>, ctrl: TickLoopControl, _time| {
+            assert_eq!(dt, 0.0025);
+            let info = ctrl.tick_info().unwrap();
+            listener_calls
+                .lock()
+                .unwrap()
+                .push((info.tick, info.substep, events.len()));
+            Ok(SumSnapshot { sum: 1 })
+        },
+        100.0,
+    );
+    let mut tick_loop = tick_loop
+        .with_substeps(4)
+        .with_substep_merge(|a, b| SumSnapshot { sum: a.sum + b.sum });
+    event_sender.send(1).unwrap();
+    event_sender.send(2).unwrap();
+    let handle = thread::spawn(move || tick_loop.start());
+    thread::sleep(Duration::from_millis(50));
+    ctrl.clone().stop();
+    handle.join().unwrap();
+
+    let calls = calls.lock().unwrap();
+    assert!(calls.len() >= 8);
+    assert_eq!(calls.len() % 4, 0);
+    for (i, &(tick, substep, events)) in calls.iter().enumerate() {
+        assert_eq!(tick, i as u64 / 4);
+        assert_eq!(substep, i as u32 % 4);
+        assert_eq!(events, if i == 0 { 2 } else { 0 });
+    }
+    let snapshots = snapshots.read().unwrap();
+    assert_eq!(snapshots.new_snapshot.unwrap().sum, 4);
+}
+
+#[test]
+fn test_timestep_modes() {
+    fn run(mode: TimestepMode) -> Vec<(f32, Duration, Duration)> {
+        let dts = Arc::new(Mutex::new(Vec::new()));
+        let listener_dts = dts.clone();
+        let (tick_loop, _, ctrl, _) = TickLoop::init(
+            move |dt, _events: Vec<()>, ctrl: TickLoopControl, _time| {
+                let info = ctrl.tick_info().unwrap();
+                listener_dts
+                    .lock()
+                    .unwrap()
+                    .push((dt, info.nominal_dt, info.measured_dt));
+                // Every tick takes longer than the tick length.
+                thread::sleep(Duration::from_millis(15));
+                Ok(SumSnapshot { sum: 0 })
+            },
+            100.0,
+        );
+        let mut tick_loop = tick_loop.with_timestep(mode);
+        let handle = thread::spawn(move || tick_loop.start());
+        thread::sleep(Duration::from_millis(100));
+        ctrl.clone().stop();
+        handle.join().unwrap();
+        Arc::try_unwrap(dts).unwrap().into_inner().unwrap()
+    }
+
+    let tick_length = Duration::from_millis(10);
+    for (dt, nominal, measured) in run(TimestepMode::Fixed).into_iter().skip(1) {
+        assert_eq!(dt, 0.01);
+        assert_eq!(nominal, tick_length);
+        assert!(measured >= Duration::from_millis(15));
+    }
+    for (dt, nominal, measured) in run(TimestepMode::Variable).into_iter().skip(1) {
+        assert_eq!(dt, measured.as_secs_f32());
+        assert_eq!(nominal, tick_length);
+        assert!(measured >= Duration::from_millis(15));
+    }
+    let clamped = run(TimestepMode::SemiFixed {
+        min: Duration::from_millis(5),
+        max: Duration::from_millis(12),
+    });
+    assert!(clamped.len() >= 3);
+    for (dt, _, _) in clamped.into_iter().skip(1) {
+        assert_eq!(dt, 0.012);
+    }
+}
+
+#[test]
+fn test_fixed_stepper() {
+    let (mut tick_loop, event_sender, mut ctrl, snapshots) = TickLoop::init(
+        |_dt, events: Vec<u64>, ctrl: TickLoopControl, _time| {
+            Ok(SumSnapshot {
+                sum: ctrl.tick_info().unwrap().tick + events.iter().sum::<u64>(),
+            })
+        },
+        100.0,
+    );
+    tick_loop.tick_length = Duration::from_millis(10);
+    let mut stepper = FixedStepper::new(tick_loop).with_max_ticks_per_frame(4);
+
+    assert_eq!(stepper.accumulate(Duration::from_millis(25)), 2);
+    stepper.tick();
+    stepper.tick();
+    assert!((stepper.alpha() - 0.5).abs() < 0.001);
+
+    event_sender.send(100).unwrap();
+    stepper.update(Duration::from_millis(5));
+    assert_eq!(stepper.tick_loop().current_tick(), 3);
+    assert_eq!(snapshots.read().unwrap().new_snapshot.unwrap().sum, 102);
+
+    // Time beyond the per-frame limit is dropped.
+    assert!(stepper.update(Duration::from_millis(100)) < 1.0);
+    assert_eq!(stepper.tick_loop().current_tick(), 7);
+
+    ctrl.pause();
+    assert_eq!(stepper.update(Duration::from_millis(100)), stepper.alpha());
+    assert_eq!(stepper.tick_loop().current_tick(), 7);
+}
+
+#[test]
+fn test_tick_stats() {
+    let (mut tick_loop, _, ctrl, _) = TickLoop::init(
+        |_dt, _events: Vec<()>, ctrl: TickLoopControl, _time| {
+            let slow = ctrl.tick_info().unwrap().tick % 10 == 5;
+            thread::sleep(Duration::from_millis(if slow { 25 } else { 2 }));
+            Ok(SumSnapshot { sum: 0 })
+        },
+        100.0,
+    );
+    assert_eq!(ctrl.stats().ticks, 0);
+    let handle = thread::spawn(move || tick_loop.start());
+    thread::sleep(Duration::from_millis(400));
+    ctrl.clone().stop();
+    handle.join().unwrap();
+
+    let stats = ctrl.stats();
+    assert!(stats.ticks >= 20);
+    assert!(stats.min <= stats.average && stats.average <= stats.max);
+    assert!(stats.min >= Duration::from_millis(2));
+    assert!(stats.max >= Duration::from_millis(25));
+    assert!(stats.percentile(50.0) <= stats.percentile(99.0));
+    assert_eq!(stats.percentile(100.0), stats.max);
+    assert!(stats.listener_time >= Duration::from_millis(2));
+    assert!(stats.sleep_time > Duration::ZERO);
+    assert!(stats.max_jitter >= Duration::from_millis(10));
+    assert!(stats.overruns >= 1);
+    assert!(stats.catch_up_bursts >= 1);
+    assert!(stats.tps > 60.0 && stats.tps < 140.0);
+    let histogram = stats.histogram(Duration::from_millis(1));
+    assert_eq!(histogram.iter().sum::<usize>(), stats.ticks);
+    assert_eq!(histogram.len(), stats.max.as_millis() as usize + 1);
+    assert_eq!(histogram[..2], [0, 0]);
+    assert_eq!(stats.durations().len(), stats.ticks);
+
+    // Counters only cover the window.
+    ctrl.set_stats_window(Duration::ZERO);
+    let stats = ctrl.stats();
+    assert!(stats.ticks <= 1);
+    assert!(stats.overruns <= 1);
+    assert_eq!(stats.catch_up_bursts, 0);
+}
+
+#[test]
+fn test_scheduler_stats() {
+    let mut scheduler = Scheduler::new(100.0);
+    let mut controls = Vec::new();
+    for divisor in [1, 2] {
+        let (tick_loop, _, ctrl, _) = TickLoop::init(
+            move |_dt, _events: Vec<()>, ctrl: TickLoopControl, _time| {
+                let tick = ctrl.tick_info().unwrap().tick;
+                if divisor == 1 && tick % 10 == 5 {
+                    thread::sleep(Duration::from_millis(35));
+                }
+                Ok(SumSnapshot { sum: 0 })
+            },
+            1.0,
+        );
+        scheduler.add_loop(tick_loop, divisor);
+        controls.push(ctrl);
+    }
+    let mut scheduler_ctrl = scheduler.control();
+    let handle = thread::spawn(move || scheduler.start());
+    thread::sleep(Duration::from_millis(400));
+    scheduler_ctrl.stop();
+    handle.join().unwrap();
+
+    // Every loop has its own window, and the scheduler's control reports its frames.
+    let (fast, slow, frames) = (
+        controls[0].stats(),
+        controls[1].stats(),
+        scheduler_ctrl.stats(),
+    );
+    assert_eq!(frames.ticks, fast.ticks);
+    assert!(slow.ticks > 0 && slow.ticks <= fast.ticks.div_ceil(2));
+    assert!(fast.overruns >= 1 && frames.overruns >= 1);
+    assert_eq!(slow.overruns, 0);
+    assert!(fast.catch_up_bursts >= 1 && frames.catch_up_bursts >= 1);
+    // The slow loop runs late behind the fast loop's long ticks.
+    assert!(slow.max_jitter >= Duration::from_millis(10));
+}
+
+#[test]
+fn test_stepper_stops_mid_frame() {
+    let (tick_loop, _, mut ctrl, _) = TickLoop::init(
+        |_dt, _events: Vec<()>, mut ctrl: TickLoopControl, _time| {
+            match ctrl.tick_info().unwrap().tick {
+                1 => ctrl.pause(),
+                3 => ctrl.stop(),
+                _ => {}
+            }
+            Ok(SumSnapshot { sum: 0 })
+        },
+        100.0,
+    );
+    let mut stepper = FixedStepper::new(tick_loop);
+
+    // The tick that pauses the loop is the last one of the frame, and the rest of the frame's time is dropped.
+    assert_eq!(stepper.update(Duration::from_millis(55)), 0.0);
+    assert_eq!(stepper.tick_loop().current_tick(), 2);
+    ctrl.resume();
+    stepper.update(Duration::ZERO);
+    assert_eq!(stepper.tick_loop().current_tick(), 2);
+
+    stepper.update(Duration::from_millis(55));
+    assert_eq!(stepper.tick_loop().current_tick(), 4);
+    assert_eq!(ctrl.state(), TickLoopState::Stopped);
+    stepper.update(Duration::from_millis(55));
+    assert_eq!(stepper.tick_loop().current_tick(), 4);
+}
+
+#[test]
+fn test_stepper_stats() {
+    let (tick_loop, _, ctrl, _) = TickLoop::init(
+        |_dt, _events: Vec<()>, _ctrl, _time| Ok(SumSnapshot { sum: 0 }),
+        100.0,
+    );
+    let mut stepper = FixedStepper::new(tick_loop);
+    stepper.update(Duration::from_millis(5));
+    stepper.update(Duration::from_millis(30));
+
+    // The frame ran three ticks back to back, the first 25ms after it was due.
+    let stats = ctrl.stats();
+    assert_eq!(stats.ticks, 3);
+    assert_eq!(stats.catch_up_bursts, 1);
+    assert!(stats.max_jitter >= Duration::from_millis(24));
+    assert!(stats.jitter >= Duration::from_millis(14));
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn test_tracing_spans() {
+    use tracing::field::{Field, Visit};
+    use tracing::{span, Event, Metadata, Subscriber};
+
+    struct TickVisitor(Option<u64>);
+    impl Visit for TickVisitor {
+        fn record_u64(&mut self, field: &Field, value: u64) {
+            if field.name() == "tick" {
+                self.0 = Some(value);
+            }
+        }
+        fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+    }
+
+    type Spans = Arc<Mutex<Vec<(&'static str, Option<u64>)>>>;
+    struct SpanRecorder(Spans);
+    impl Subscriber for SpanRecorder {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+            let mut visitor = TickVisitor(None);
+            span.record(&mut visitor);
+            let mut spans = self.0.lock().unwrap();
+            spans.push((span.metadata().name(), visitor.0));
+            span::Id::from_u64(spans.len() as u64)
+        }
+        fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+        fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+        fn event(&self, _event: &Event<'_>) {}
+        fn enter(&self, _span: &span::Id) {}
+        fn exit(&self, _span: &span::Id) {}
+    }
+
+    let (tick_loop, _, _, _) = TickLoop::init(
+        |_dt, _events: Vec<()>, _ctrl, _time| Ok(SumSnapshot { sum: 0 }),
+        100.0,
+    );
+    let mut stepper = FixedStepper::new(tick_loop.with_substeps(2));
+    let spans = Arc::new(Mutex::new(Vec::new()));
+    tracing::subscriber::with_default(SpanRecorder(spans.clone()), || {
+        stepper.tick();
+        stepper.tick();
+    });
+
+    let expected: Vec<_> = (0..2)
+        .flat_map(|tick| {
+            [
+                "tick",
+                "drain_events",
+                "listener",
+                "listener",
+                "publish_snapshot",
+            ]
+            .map(|name| (name, Some(tick)))
+        })
+        .collect();
+    assert_eq!(*spans.lock().unwrap(), expected);
+}
+
+#[test]
+fn test_scheduler() {
+    let (order, thread_ids) = run_scheduler(1);
+    assert_eq!(thread_ids, HashSet::from([thread::current().id()]));
+    let names: String = order.iter().map(|(name, _)| name).collect();
+    assert_eq!(names, "abcaabaabcaaba");
+    for (name, dt) in order {
+        let expected = match name {
+            'a' => 0.001,
+            'b' => 0.002,
+            _ => 0.004,
+        };
+        assert_close(dt, expected);
+    }
+
+    // The workers are spawned once, not on every frame. Frames with a single due loop run it on the scheduler's thread.
+    let (order, thread_ids) = run_scheduler(3);
+    assert_eq!(order.len(), 14);
+    assert!(thread_ids.len() <= 3 + 1);
+}
+
+#[test]
+fn test_input_state() {
+    let mut input = InputState::new();
+
+    input.update([
+        InputEvent::Down('a'),
+        InputEvent::Down('b'),
+        InputEvent::Up('b'),
+    ]);
+    assert!(input.is_pressed(&'a') && input.is_held(&'a'));
+    assert!(input.is_pressed(&'b') && input.is_released(&'b') && !input.is_held(&'b'));
+
+    // Key repeat does not press the key again.
+    input.update([InputEvent::Down('a')]);
+    assert!(!input.is_pressed(&'a') && input.is_held(&'a'));
+    assert_eq!(input.released().count(), 0);
+
+    input.update([InputEvent::Up('a'), InputEvent::Up('c')]);
+    assert!(input.is_released(&'a') && !input.is_held(&'a'));
+    assert!(!input.is_released(&'c'));
+}
+
+#[test]
+fn test_mpsc_event_source() {
+    let (sender, reciever) = std::sync::mpsc::channel();
+    let tick_loop = TickLoop::new(
+        |_dt, events: Vec<u64>, _ctrl, _time| {
+            Ok(SumSnapshot {
+                sum: events.iter().sum(),
+            })
+        },
+        100.0,
+        reciever,
+        Arc::new(RwLock::new(Snapshots::new())),
+    );
+    let snapshots = tick_loop.snapshots();
+    sender.send(1).unwrap();
+    sender.send(2).unwrap();
+    FixedStepper::new(tick_loop).tick();
+    assert_eq!(snapshots.read().unwrap().new_snapshot.unwrap().sum, 3);
+}
+
+#[test]
+fn test_late_envelopes_keep_their_target() {
+    let (sender, mut reciever) = event::channel();
+    sender.send_at(2, 'a').unwrap();
+    sender.send('b').unwrap();
+    sender.send_at(7, 'c').unwrap();
+
+    let envelopes = reciever.envelopes_for(5, |_| 0);
+    assert_eq!(
+        envelopes,
+        vec![
+            Envelope::new(EventTarget::Tick(2), 'a'),
+            Envelope::new(EventTarget::Tick(5), 'b'),
+        ]
+    );
+    assert_eq!(envelopes[0].tick(), Some(2));
+    assert_eq!(reciever.events_for(7, |_| 0), vec!['c']);
+}
+
+#[test]
+fn test_rollback_applies_late_events() {
+    let calls = Arc::new(AtomicU32::new(0));
+    let listener_calls = calls.clone();
+    let ticks = Arc::new(Mutex::new(Vec::new()));
+    let listener_ticks = ticks.clone();
+    let (mut tick_loop, _, _, snapshots) = Rollback::init(
+        0u64,
+        move |sum: &mut u64, _dt, events: Vec<u64>, ctrl: TickLoopControl, _time| {
+            listener_calls.fetch_add(1, Ordering::SeqCst);
+            listener_ticks
+                .lock()
+                .unwrap()
+                .push(ctrl.tick_info().unwrap().tick);
+            *sum += events.iter().sum::<u64>();
+            Ok(SumSnapshot { sum: *sum })
+        },
+        1000.0,
+        2,
+    );
+    let stamped = |tick, events: Vec<(u64, u64)>| RecordedTick {
+        tick,
+        events: events
+            .into_iter()
+            .map(|(tick, event)| Envelope::new(EventTarget::Tick(tick), event))
+            .collect(),
+    };
+
+    // Tick 1 learns about an event for tick 0, and schedules one for tick 3.
+    tick_loop.replay(vec![stamped(0, vec![]), stamped(1, vec![(0, 1), (3, 100)])]);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    // Tick 0 runs again with its own tick info.
+    assert_eq!(*ticks.lock().unwrap(), vec![0, 0, 1]);
+    {
+        let snapshots = snapshots.read().unwrap();
+        assert_eq!(snapshots.last_snapshot.unwrap().sum, 1);
+        assert_eq!(snapshots.new_snapshot.unwrap().sum, 1);
+    }
+
+    // Tick 4 corrects tick 2, and the event for tick 0 is now outside of the rollback window.
+    tick_loop.replay(vec![stamped(4, vec![(2, 10), (0, 1000)])]);
+    assert_eq!(calls.load(Ordering::SeqCst), 3 + 2 + 2 + 1);
+    let snapshots = snapshots.read().unwrap();
+    assert_eq!(snapshots.last_snapshot.unwrap().sum, 111);
+    assert_eq!(snapshots.new_snapshot.unwrap().sum, 111);
+}
+
+#[test]
+fn test_rollback_with_substeps() {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let listener_calls = calls.clone();
+    let (tick_loop, _, _, snapshots) = Rollback::init(
+        0u64,
+        move |sum: &mut u64, _dt, events: Vec<u64>, ctrl: TickLoopControl, _time| {
+            let info = ctrl.tick_info().unwrap();
+            listener_calls
+                .lock()
+                .unwrap()
+                .push((info.tick, info.substep, events.clone()));
+            *sum += events.iter().sum::<u64>();
+            Ok(SumSnapshot { sum: *sum })
+        },
+        1000.0,
+        2,
+    );
+    let mut tick_loop = tick_loop.with_substeps(2);
+    let stamped = |tick, events: Vec<(u64, u64)>| RecordedTick {
+        tick,
+        events: events
+            .into_iter()
+            .map(|(tick, event)| Envelope::new(EventTarget::Tick(tick), event))
+            .collect(),
+    };
+
+    // The late event for tick 1 rolls back to its first sub-step, not to the second sub-step of tick 0.
+    tick_loop.replay(vec![
+        stamped(0, vec![]),
+        stamped(1, vec![]),
+        stamped(2, vec![(1, 5)]),
+    ]);
+    assert_eq!(
+        *calls.lock().unwrap(),
+        vec![
+            (0, 0, vec![]),
+            (0, 1, vec![]),
+            (1, 0, vec![]),
+            (1, 1, vec![]),
+            (1, 0, vec![5]),
+            (1, 1, vec![]),
+            (2, 0, vec![]),
+            (2, 1, vec![]),
+        ]
+    );
+    assert_eq!(snapshots.read().unwrap().new_snapshot.unwrap().sum, 5);
+}
+
+#[test]
+fn test_tick_time_conversion() {
+    let reference = Instant::now();
+    let time = TickTime::from_instant(3, reference + Duration::from_millis(50), reference);
+    assert_eq!(time, TickTime::new(3, Duration::from_millis(50)));
+
+    let later = reference + Duration::from_secs(1);
+    assert_eq!(time.to_instant(later) - later, Duration::from_millis(50));
+    assert_eq!(
+        TickTime::from_instant(0, reference, later).offset,
+        Duration::ZERO
+    );
+}
+
+#[cfg(feature = "serde")]
+#[derive(Debug, Interpolate, serde::Serialize, serde::Deserialize)]
+struct SerdeSnapshot {
+    #[serde(with = "saunter::time::serde_instant")]
+    time: Instant,
+    value: f32,
+}
+
+#[cfg(feature = "serde")]
+impl Snapshot for SerdeSnapshot {
+    fn get_time(&self) -> &Instant {
+        &self.time
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_snapshots_and_recordings() {
+    let now = Instant::now();
+    let mut snapshots = Snapshots::new();
+    snapshots.update(SerdeSnapshot {
+        time: now,
+        value: 1.0,
+    });
+    snapshots.update(SerdeSnapshot {
+        time: now + Duration::from_millis(16),
+        value: 2.0,
+    });
+
+    let json = serde_json::to_string(&snapshots).unwrap();
+    let loaded: Snapshots<SerdeSnapshot> = serde_json::from_str(&json).unwrap();
+    let (last, new) = (loaded.last_snapshot.unwrap(), loaded.new_snapshot.unwrap());
+    assert_eq!(new.time - last.time, Duration::from_millis(16));
+    assert_eq!(new.value, 2.0);
+
+    let recording = Recording {
+        ticks: vec![
+            RecordedTick {
+                tick: 0,
+                events: vec![1u64, 2],
+            },
+            RecordedTick {
+                tick: 1,
+                events: vec![],
+            },
+        ],
+    };
+    let json = serde_json::to_string(&recording).unwrap();
+    assert_eq!(
+        serde_json::from_str::<Recording<u64>>(&json).unwrap(),
+        recording
+    );
+
+    let time = TickTime::new(7, Duration::from_millis(110));
+    let json = serde_json::to_string(&time).unwrap();
+    assert_eq!(serde_json::from_str::<TickTime>(&json).unwrap(), time);
+
+    let easing = Easing::Steps(4).reverse();
+    let json = serde_json::to_string(&easing).unwrap();
+    assert_eq!(json, "\"reverse(steps(4))\"");
+    assert_eq!(serde_json::from_str::<Easing>(&json).unwrap(), easing);
+    assert!(serde_json::to_string(&Easing::custom(|t| t)).is_err());
+    assert!(serde_json::from_str::<Easing>("\"ease_sideways\"").is_err());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_colors_and_tick_info() {
+    let track = KeyframeTrack::new(vec![
+        Keyframe::new(0.0, Rgb::from_rgb8(255, 0, 0)),
+        Keyframe::new(1.0, Rgb::new(0.0, 0.5, 1.0)).with_easing(Easing::Steps(3)),
+    ]);
+    let json = serde_json::to_string(&track).unwrap();
+    assert_eq!(
+        serde_json::from_str::<KeyframeTrack<Rgb>>(&json).unwrap(),
+        track
+    );
+
+    let colors = (
+        Rgb::new(0.1, 0.2, 0.3).with_alpha(0.5),
+        Oklab {
+            l: 0.6,
+            a: 0.1,
+            b: -0.1,
+        },
+        Hsv::new(120.0, 0.5, 0.25),
+    );
+    let json = serde_json::to_string(&colors).unwrap();
+    assert_eq!(
+        serde_json::from_str::<(saunter::color::Rgba, Oklab, Hsv)>(&json).unwrap(),
+        colors
+    );
+
+    let now = Instant::now();
+    let info = TickInfo {
+        tick: 42,
+        substep: 1,
+        time: now,
+        nominal_dt: Duration::from_millis(10),
+        measured_dt: Duration::from_millis(11),
+        wall_time: now + Duration::from_millis(3),
+        time_scale: 0.5,
+    };
+    let json = serde_json::to_string(&info).unwrap();
+    assert_eq!(serde_json::from_str::<TickInfo>(&json).unwrap(), info);
 }